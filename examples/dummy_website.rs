@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Instant;
+use rust_http::{
+    CachePolicy, EndpointHandler, ErrorPage, HttpMethod, HttpRequest, Middleware, Next, RequestArgValue, ResourceHandler,
+    ResourceParameter, Problem, ResourceParameterLocation, Response, StatusClass, StatusCode, TemplateEngine, WebServer,
+};
+
+/// Allows cross-origin requests, preflight requests are answered without reaching the endpoint.
+struct Cors {
+    origin: String,
+}
+
+impl Middleware for Cors {
+    fn handle(&self, request: &mut HttpRequest, next: Next) -> Response {
+        if request.general.method == HttpMethod::Options && request.headers.contains_key("access-control-request-method") {
+            return Response::no_content()
+                .header("Access-Control-Allow-Origin", &self.origin)
+                .header("Access-Control-Allow-Methods", "GET, HEAD")
+                .header("Access-Control-Max-Age", "600");
+        }
+        next.run(request).header("Access-Control-Allow-Origin", &self.origin)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let template_engine = TemplateEngine {};
+    let greet_engine = template_engine.clone();
+    let mut endpoint_handler = EndpointHandler::create();
+    endpoint_handler.register_static_with_cache(
+        String::from("files/dummy-website"),
+        String::from("website"),
+        CachePolicy::NoCache,
+    )?;
+    endpoint_handler.register_assets_with_cache(
+        String::from("files/storage/"),
+        String::from("storage"),
+        CachePolicy::MaxAge(3600),
+    )?;
+    endpoint_handler.register_error_page(
+        StatusCode::NOT_FOUND,
+        ErrorPage::File(PathBuf::from("files/errors/404.html")),
+    );
+    endpoint_handler.register_error_class_page(
+        StatusClass::ServerError,
+        ErrorPage::Template(String::from("<h1>${status} ${reason}</h1><p>Please try again later.</p>\r\n")),
+    );
+    endpoint_handler.register_resource(
+        HttpMethod::Get,
+        String::from("math/sqr"),
+        String::from("sqr"),
+        Box::new(ResourceHandler::new(
+            Box::from({move |params: &HashMap<&str, &RequestArgValue>, request: &HttpRequest| {
+                let template = "<div><span>${n} * ${n}</span> = <span>${sqr}</span></div>\r\n";
+                // n is validated as i8 before the handler is called, the square needs a wider type.
+                let n = i16::from(params.get("n").unwrap().value.parse::<i8>().unwrap());
+                let res = (n * n).to_string();
+                let response = match request.preferred_media_type(&["text/html", "application/json"]) {
+                    Some("application/json") => Response::json(&format!("{{\"n\":{},\"sqr\":{}}}", n, res)),
+                    Some(_) => {
+                        let context: HashMap<String, String> = HashMap::from([("sqr".to_string(), res), ("n".to_string(), n.to_string())]);
+                        Response::html(&template_engine.render(template, context))
+                    }
+                    None => Problem::create(StatusCode::NOT_ACCEPTABLE).to_response(None),
+                };
+                response.header("Vary", "Accept")
+            }}),
+            vec![ResourceParameter::p_i8(
+                String::from("n"),
+                ResourceParameterLocation::Query,
+            )],
+        )),
+    )?;
+    endpoint_handler.register_resource(
+        HttpMethod::Post,
+        String::from("echo"),
+        String::from("echo"),
+        Box::new(ResourceHandler::new(
+            Box::from(|_: &HashMap<&str, &RequestArgValue>, request: &HttpRequest| {
+                match request.body_str() {
+                    Ok(body) => Response::text(body),
+                    Err(_) => Response::create(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+                }
+            }),
+            vec![],
+        )),
+    )?;
+    endpoint_handler.register_resource(
+        HttpMethod::Get,
+        String::from("greet/{name}"),
+        String::from("greet"),
+        Box::new(ResourceHandler::new(
+            Box::from(move |params: &HashMap<&str, &RequestArgValue>, _: &HttpRequest| {
+                let context = HashMap::from([("name".to_string(), params.get("name").unwrap().value.to_string())]);
+                Response::html(&greet_engine.render("<div>Hello ${name}!</div>\r\n", context))
+            }),
+            vec![ResourceParameter::p_string(
+                String::from("name"),
+                ResourceParameterLocation::Path,
+            )],
+        )),
+    )?;
+    endpoint_handler.register_resource(
+        HttpMethod::Get,
+        String::from("tags"),
+        String::from("tags"),
+        Box::new(ResourceHandler::new(
+            Box::from(|_: &HashMap<&str, &RequestArgValue>, request: &HttpRequest| {
+                let tags = request.query_all("tag").join(", ");
+                match request.has_query("upper") {
+                    true => Response::text(&tags.to_uppercase()),
+                    false => Response::text(&tags),
+                }
+            }),
+            vec![],
+        )),
+    )?;
+    endpoint_handler.register_middleware(Box::new(|request: &mut HttpRequest, next: Next| {
+        let start = Instant::now();
+        let response = next.run(request);
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+        response.header("Server-Timing", &format!("app;dur={:.1}", duration))
+    }));
+    endpoint_handler.register_middleware_for(String::from("math"), Box::new(Cors { origin: String::from("*") }));
+
+    let mut server = WebServer::builder()
+        .address("127.0.0.1")
+        .port(8080)
+        .thread_limit(4)
+        .queue_size(16)
+        .endpoint_handler(endpoint_handler)
+        .build()?;
+    let shutdown_handle = server.shutdown_handle();
+    ctrlc::set_handler(move || shutdown_handle.shutdown())?;
+    server.run()?;
+    Ok(())
+}
@@ -0,0 +1,257 @@
+pub mod body {
+    use crate::status::status::StatusCode;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fmt::Formatter;
+
+    pub enum BodyLength {
+        Empty,
+        ContentLength(usize),
+        Chunked(ChunkedDecoder),
+    }
+
+    /// Progress of a chunked body that is decoded while it arrives. Complete chunks are
+    /// only decoded once, each read continues where the previous one stopped.
+    #[derive(Debug, Default)]
+    pub struct ChunkedDecoder {
+        body: Vec<u8>,
+        /// Start of the next chunk or trailer line in the received data.
+        position: usize,
+        /// Start of the trailer section once the last chunk has been read.
+        trailer_start: Option<usize>,
+    }
+
+    /// Longest chunk size or trailer line, chunk extensions included.
+    const MAX_CHUNK_LINE: usize = 4 * 1024;
+
+    #[derive(Debug, PartialEq)]
+    pub enum BodyError {
+        Invalid(String),
+        /// The body exceeds the configured maximum size.
+        TooLarge,
+    }
+
+    impl BodyError {
+        /// Status code of the response rejecting the request.
+        pub fn status(&self) -> StatusCode {
+            match self {
+                BodyError::Invalid(_) => StatusCode::BAD_REQUEST,
+                BodyError::TooLarge => StatusCode::CONTENT_TOO_LARGE,
+            }
+        }
+    }
+
+    impl fmt::Display for BodyError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                BodyError::Invalid(reason) => write!(f, "{}", reason),
+                BodyError::TooLarge => write!(f, "Request body too large"),
+            }
+        }
+    }
+
+    pub fn find_header_end(received: &[u8]) -> Option<usize> {
+        received.windows(4).position(|it| it == b"\r\n\r\n")
+    }
+
+    /// Determines the framing of the body, a declared length above `max_size` is rejected
+    /// before any of the body is read.
+    pub fn body_length(headers: &HashMap<String, String>, max_size: usize) -> Result<BodyLength, BodyError> {
+        if let Some(transfer_encoding) = headers.get("transfer-encoding") {
+            let last_encoding = transfer_encoding.split(',').map(|it| it.trim()).next_back();
+            return match last_encoding {
+                Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => Ok(BodyLength::Chunked(ChunkedDecoder::default())),
+                _ => Err(BodyError::Invalid(format!("Unsupported transfer encoding: {}", transfer_encoding))),
+            };
+        }
+        match headers.get("content-length") {
+            Some(length) => match length.trim().parse::<usize>() {
+                Ok(0) => Ok(BodyLength::Empty),
+                Ok(l) if l > max_size => Err(BodyError::TooLarge),
+                Ok(l) => Ok(BodyLength::ContentLength(l)),
+                Err(_) => Err(BodyError::Invalid(format!("Invalid content length: {}", length))),
+            },
+            None => Ok(BodyLength::Empty),
+        }
+    }
+
+    /// Whether the request has both `Transfer-Encoding` and `Content-Length`. Its body is framed
+    /// by the transfer encoding, but the connection has to be closed after it, as the conflict
+    /// may be an attempt at request smuggling, RFC 9112 section 6.1.
+    pub fn has_conflicting_framing(headers: &HashMap<String, String>) -> bool {
+        headers.contains_key("transfer-encoding") && headers.contains_key("content-length")
+    }
+
+    /// Tries to read the body from the bytes following the headers, `data` has to start at
+    /// the same byte on every call. Returns the body and the number of consumed bytes, or
+    /// `None` if more data is needed.
+    pub fn read_body(body_length: &mut BodyLength, data: &[u8], max_size: usize) -> Result<Option<(Vec<u8>, usize)>, BodyError> {
+        match body_length {
+            BodyLength::Empty => Ok(Some((vec![], 0))),
+            BodyLength::ContentLength(length) => match data.len() >= *length {
+                true => Ok(Some((data[..*length].to_vec(), *length))),
+                false => Ok(None),
+            },
+            BodyLength::Chunked(decoder) => decoder.decode(data, max_size),
+        }
+    }
+
+    impl ChunkedDecoder {
+        /// Decodes a chunked body. Chunk sizes are checked against `max_size` as soon as they
+        /// arrive, the trailer section may be as long as the body limit allows.
+        fn decode(&mut self, data: &[u8], max_size: usize) -> Result<Option<(Vec<u8>, usize)>, BodyError> {
+            while self.trailer_start.is_none() {
+                let position = self.position;
+                let line_end = match find_line_end(&data[position..])? {
+                    Some(i) => position + i,
+                    None => return Ok(None),
+                };
+                let size_line = std::str::from_utf8(&data[position..line_end])
+                    .map_err(|_| BodyError::Invalid(String::from("Invalid chunk size")))?;
+                let size_str = size_line.split(';').next().unwrap_or("").trim();
+                let size = usize::from_str_radix(size_str, 16)
+                    .map_err(|_| BodyError::Invalid(format!("Invalid chunk size: {}", size_line)))?;
+                if size > max_size - self.body.len() {
+                    return Err(BodyError::TooLarge);
+                }
+                let chunk_start = line_end + 2;
+                if size == 0 {
+                    self.position = chunk_start;
+                    self.trailer_start = Some(chunk_start);
+                    break;
+                }
+                // An incomplete chunk is decoded again from its size line once more data arrived.
+                if data.len() < chunk_start + size + 2 {
+                    return Ok(None);
+                }
+                if &data[chunk_start + size..chunk_start + size + 2] != b"\r\n" {
+                    return Err(BodyError::Invalid(String::from("Chunk is not terminated by CRLF")));
+                }
+                self.body.extend_from_slice(&data[chunk_start..chunk_start + size]);
+                self.position = chunk_start + size + 2;
+            }
+            // Trailer fields are skipped, the body ends with an empty line.
+            let trailer_start = self.trailer_start.unwrap_or(self.position);
+            loop {
+                if self.position - trailer_start > max_size {
+                    return Err(BodyError::TooLarge);
+                }
+                let line_end = match find_line_end(&data[self.position..])? {
+                    Some(i) => self.position + i,
+                    None => return Ok(None),
+                };
+                let is_empty = line_end == self.position;
+                self.position = line_end + 2;
+                if is_empty {
+                    return Ok(Some((std::mem::take(&mut self.body), self.position)));
+                }
+            }
+        }
+    }
+
+    /// Position of the next CRLF, lines longer than `MAX_CHUNK_LINE` are rejected.
+    fn find_line_end(data: &[u8]) -> Result<Option<usize>, BodyError> {
+        // Only the start of the data can hold a line that is short enough.
+        let candidate = &data[..data.len().min(MAX_CHUNK_LINE + 2)];
+        match candidate.windows(2).position(|it| it == b"\r\n") {
+            Some(i) if i <= MAX_CHUNK_LINE => Ok(Some(i)),
+            None if data.len() <= MAX_CHUNK_LINE => Ok(None),
+            _ => Err(BodyError::Invalid(String::from("Chunk line too long"))),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const BODY: &[u8] = b"5;name=value\r\nhello\r\n7\r\n, world\r\n0\r\nX-Trailer: 1\r\n\r\n";
+
+        fn chunked() -> BodyLength {
+            BodyLength::Chunked(ChunkedDecoder::default())
+        }
+
+        #[test]
+        fn decodes_chunks_with_extensions_and_trailers() {
+            let mut data = BODY.to_vec();
+            data.extend_from_slice(b"GET / HTTP/1.1\r\n");
+            assert_eq!(read_body(&mut chunked(), &data, 1024), Ok(Some((b"hello, world".to_vec(), BODY.len()))));
+        }
+
+        #[test]
+        fn decodes_a_body_arriving_byte_by_byte() {
+            let mut body_length = chunked();
+            for end in 0..BODY.len() {
+                assert_eq!(read_body(&mut body_length, &BODY[..end], 1024), Ok(None), "complete after {} bytes", end);
+            }
+            assert_eq!(read_body(&mut body_length, BODY, 1024), Ok(Some((b"hello, world".to_vec(), BODY.len()))));
+        }
+
+        #[test]
+        fn decodes_a_body_split_at_every_offset() {
+            for split in 0..BODY.len() {
+                let mut body_length = chunked();
+                assert_eq!(read_body(&mut body_length, &BODY[..split], 1024), Ok(None));
+                assert_eq!(
+                    read_body(&mut body_length, BODY, 1024),
+                    Ok(Some((b"hello, world".to_vec(), BODY.len()))),
+                    "split at {}",
+                    split
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_chunks_without_crlf() {
+            let result = read_body(&mut chunked(), b"5\r\nhelloX\r\n0\r\n\r\n", 1024);
+            assert!(matches!(result, Err(BodyError::Invalid(_))));
+        }
+
+        #[test]
+        fn rejects_invalid_chunk_sizes() {
+            for data in [&b"x\r\n"[..], b"\r\n", b"-5\r\n"] {
+                assert!(matches!(read_body(&mut chunked(), data, 1024), Err(BodyError::Invalid(_))));
+            }
+        }
+
+        #[test]
+        fn rejects_bodies_above_the_limit() {
+            assert_eq!(read_body(&mut chunked(), b"5\r\nhello\r\n", 4), Err(BodyError::TooLarge));
+            // The size is checked before the chunk arrives.
+            assert_eq!(read_body(&mut chunked(), b"3\r\nabc\r\n3\r\n", 5), Err(BodyError::TooLarge));
+            assert_eq!(read_body(&mut chunked(), b"ffffffffffffffff\r\n", 1024), Err(BodyError::TooLarge));
+        }
+
+        #[test]
+        fn rejects_oversized_chunk_lines() {
+            let mut line = b"5;".to_vec();
+            line.extend(vec![b'x'; MAX_CHUNK_LINE]);
+            assert!(matches!(read_body(&mut chunked(), &line, 1024), Err(BodyError::Invalid(_))));
+            line.extend_from_slice(b"\r\nhello\r\n0\r\n\r\n");
+            assert!(matches!(read_body(&mut chunked(), &line, 1024), Err(BodyError::Invalid(_))));
+        }
+
+        #[test]
+        fn reads_content_length_bodies() {
+            let mut body_length = BodyLength::ContentLength(5);
+            assert_eq!(read_body(&mut body_length, b"hel", 1024), Ok(None));
+            assert_eq!(read_body(&mut body_length, b"helloGET", 1024), Ok(Some((b"hello".to_vec(), 5))));
+        }
+
+        #[test]
+        fn determines_the_framing_from_the_headers() {
+            let headers = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+                pairs.iter().map(|(name, value)| (String::from(*name), String::from(*value))).collect()
+            };
+            assert!(matches!(body_length(&headers(&[]), 10), Ok(BodyLength::Empty)));
+            assert!(matches!(body_length(&headers(&[("content-length", "0")]), 10), Ok(BodyLength::Empty)));
+            assert!(matches!(body_length(&headers(&[("content-length", "10")]), 10), Ok(BodyLength::ContentLength(10))));
+            assert_eq!(body_length(&headers(&[("content-length", "11")]), 10).err(), Some(BodyError::TooLarge));
+            assert!(matches!(body_length(&headers(&[("content-length", "ten")]), 10), Err(BodyError::Invalid(_))));
+            assert!(matches!(body_length(&headers(&[("transfer-encoding", "gzip, chunked")]), 10), Ok(BodyLength::Chunked(_))));
+            assert!(matches!(body_length(&headers(&[("transfer-encoding", "chunked, gzip")]), 10), Err(BodyError::Invalid(_))));
+            let conflicting = headers(&[("transfer-encoding", "chunked"), ("content-length", "5")]);
+            assert!(matches!(body_length(&conflicting, 10), Ok(BodyLength::Chunked(_))));
+            assert!(has_conflicting_framing(&conflicting));
+        }
+    }
+}
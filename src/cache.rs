@@ -0,0 +1,142 @@
+pub mod cache {
+    use crate::http_date::http_date::{format_http_date, parse_http_date, truncate_to_seconds};
+    use crate::types::types::{HttpMethod, HttpRequest};
+    use std::fs::Metadata;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// `Cache-Control` sent with the files of a static or asset registration.
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub enum CachePolicy {
+        /// No `Cache-Control` header, caches apply their heuristics.
+        #[default]
+        Unspecified,
+        NoStore,
+        /// Caches have to revalidate with the server before reusing a response.
+        NoCache,
+        /// Responses may be reused for the given number of seconds.
+        MaxAge(u64),
+        /// For fingerprinted files that never change under the same url.
+        Immutable(u64),
+        Custom(String),
+    }
+
+    impl CachePolicy {
+        pub fn header_value(&self) -> Option<String> {
+            match self {
+                CachePolicy::Unspecified => None,
+                CachePolicy::NoStore => Some(String::from("no-store")),
+                CachePolicy::NoCache => Some(String::from("no-cache")),
+                CachePolicy::MaxAge(seconds) => Some(format!("public, max-age={}", seconds)),
+                CachePolicy::Immutable(seconds) => Some(format!("public, max-age={}, immutable", seconds)),
+                CachePolicy::Custom(value) => Some(value.clone()),
+            }
+        }
+    }
+
+    /// Validators of a file, the entity tag is derived from its size and modification time.
+    #[derive(Debug, Clone)]
+    pub struct Validators {
+        pub etag: String,
+        pub last_modified: Option<SystemTime>,
+    }
+
+    impl Validators {
+        pub fn from_metadata(metadata: &Metadata) -> Validators {
+            let last_modified = metadata.modified().ok();
+            let modified_nanos = last_modified
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            Validators {
+                etag: format!("\"{:x}-{:x}\"", modified_nanos, metadata.len()),
+                last_modified: last_modified.map(truncate_to_seconds),
+            }
+        }
+
+        pub fn last_modified_header(&self) -> Option<String> {
+            self.last_modified.map(format_http_date)
+        }
+
+        /// Validators of the file sent with a content encoding.
+        pub fn encoded(&self, encoding: &str) -> Validators {
+            Validators {
+                etag: encoded_entity_tag(&self.etag, encoding),
+                last_modified: self.last_modified,
+            }
+        }
+    }
+
+    /// Entity tag of a representation sent with a content encoding. Its bytes differ from
+    /// the unencoded ones, so it must not share their strong tag (RFC 9110 section 8.8.3).
+    pub fn encoded_entity_tag(etag: &str, encoding: &str) -> String {
+        match etag.trim().strip_suffix('"') {
+            Some(opaque) => format!("{}-{}\"", opaque, encoding),
+            None => String::from(etag),
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Precondition {
+        Proceed,
+        /// 304, the client's copy is still valid.
+        NotModified,
+        /// 412, a condition of the client does not hold.
+        Failed,
+    }
+
+    /// Evaluates the conditional headers in the order given by RFC 9110 section 13.2.2.
+    /// The date based conditions are only checked if the matching entity tag condition
+    /// is absent.
+    pub fn evaluate_preconditions(request: &HttpRequest, validators: &Validators) -> Precondition {
+        let header = |name: &str| request.headers.get(name).map(|value| value.as_str());
+        let safe_method = matches!(request.general.method, HttpMethod::Get | HttpMethod::Head);
+        let modified_after = |name: &str| {
+            header(name)
+                .and_then(parse_http_date)
+                .map(|date| validators.last_modified.is_some_and(|modified| modified > date))
+        };
+        let precondition_failed = match header("if-match") {
+            Some(if_match) => !matches_any(if_match, &validators.etag, true),
+            None => modified_after("if-unmodified-since") == Some(true),
+        };
+        if precondition_failed {
+            return Precondition::Failed;
+        }
+        let not_modified = match header("if-none-match") {
+            Some(if_none_match) => matches_any(if_none_match, &validators.etag, false),
+            None if safe_method => modified_after("if-modified-since") == Some(false),
+            None => false,
+        };
+        match (not_modified, safe_method) {
+            (true, true) => Precondition::NotModified,
+            (true, false) => Precondition::Failed,
+            (false, _) => Precondition::Proceed,
+        }
+    }
+
+    /// Evaluates an `If-Range` header, either a strong entity tag or the exact modification date.
+    pub fn if_range_matches(header: &str, validators: &Validators) -> bool {
+        let header = header.trim();
+        if header.starts_with('"') || header.starts_with("W/") {
+            return entity_tags_match(header, &validators.etag, true);
+        }
+        match (parse_http_date(header), validators.last_modified) {
+            (Some(date), Some(last_modified)) => date == last_modified,
+            _ => false,
+        }
+    }
+
+    /// Checks a comma separated list of entity tags or `*` against the current tag.
+    fn matches_any(header: &str, etag: &str, strong: bool) -> bool {
+        header.trim() == "*" || header.split(',').any(|tag| entity_tags_match(tag.trim(), etag, strong))
+    }
+
+    /// The strong comparison fails for weak tags, the weak one ignores the weakness indicator.
+    fn entity_tags_match(tag: &str, etag: &str, strong: bool) -> bool {
+        match (tag.strip_prefix("W/"), etag.strip_prefix("W/")) {
+            (None, None) => tag == etag,
+            _ if strong => false,
+            (tag_opaque, etag_opaque) => tag_opaque.unwrap_or(tag) == etag_opaque.unwrap_or(etag),
+        }
+    }
+}
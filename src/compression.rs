@@ -0,0 +1,104 @@
+pub mod compression {
+
+    /// Effort spent on compressing on the fly, mapped to the levels of each encoding.
+    #[derive(Debug, Copy, Clone, PartialEq, Default)]
+    pub enum CompressionLevel {
+        Fastest,
+        #[default]
+        Default,
+        Best,
+    }
+
+    /// Decides which responses are compressed. Small bodies and media that is already
+    /// compressed are sent as they are, since compressing them costs more than it saves.
+    #[derive(Debug, Clone)]
+    pub struct CompressionPolicy {
+        min_size: u64,
+        mime_types: Vec<String>,
+        level: CompressionLevel,
+        precompressed: bool,
+    }
+
+    impl CompressionPolicy {
+        pub fn create() -> CompressionPolicy {
+            let mime_types = [
+                "text/",
+                "application/json",
+                "application/ld+json",
+                "application/manifest+json",
+                "application/problem+json",
+                "application/javascript",
+                "application/xml",
+                "application/xhtml+xml",
+                "application/rss+xml",
+                "application/atom+xml",
+                "application/wasm",
+                "application/vnd.ms-fontobject",
+                "font/ttf",
+                "font/otf",
+                "image/svg+xml",
+                "image/bmp",
+                "image/vnd.microsoft.icon",
+            ];
+            CompressionPolicy {
+                min_size: 1024,
+                mime_types: mime_types.iter().map(|it| String::from(*it)).collect(),
+                level: CompressionLevel::Default,
+                precompressed: true,
+            }
+        }
+
+        /// Bodies with fewer bytes are sent unencoded, defaults to 1024.
+        pub fn min_size(mut self, min_size: u64) -> CompressionPolicy {
+            self.min_size = min_size;
+            self
+        }
+
+        /// Adds a compressible type, an entry ending with `/` like `text/` covers all its subtypes.
+        pub fn mime_type(mut self, mime_type: &str) -> CompressionPolicy {
+            self.mime_types.push(mime_type.to_ascii_lowercase());
+            self
+        }
+
+        pub fn level(mut self, level: CompressionLevel) -> CompressionPolicy {
+            self.level = level;
+            self
+        }
+
+        /// Serves `foo.js.br`, `foo.js.zst` or `foo.js.gz` instead of compressing `foo.js`
+        /// on the fly, if such a sibling exists. Enabled by default.
+        pub fn precompressed(mut self, precompressed: bool) -> CompressionPolicy {
+            self.precompressed = precompressed;
+            self
+        }
+
+        pub fn get_level(&self) -> CompressionLevel {
+            self.level
+        }
+
+        pub fn serves_precompressed(&self) -> bool {
+            self.precompressed
+        }
+
+        /// Whether a body of the type is worth compressing, bodies of unknown length are.
+        pub fn compresses(&self, content_type: Option<&str>, length: Option<u64>) -> bool {
+            if length.is_some_and(|length| length < self.min_size) {
+                return false;
+            }
+            let mime_type = match content_type {
+                Some(content_type) => content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase(),
+                None => return false,
+            };
+            self.mime_types.iter().any(|allowed| match allowed.ends_with('/') {
+                true => mime_type.starts_with(allowed.as_str()),
+                false => mime_type == *allowed,
+            })
+        }
+    }
+
+    impl Default for CompressionPolicy {
+        fn default() -> CompressionPolicy {
+            CompressionPolicy::create()
+        }
+    }
+}
@@ -0,0 +1,108 @@
+pub mod encoding {
+    use std::fmt;
+    use std::fmt::{Display, Formatter};
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub enum ContentEncoding {
+        Identity,
+        Brotli,
+        Zstd,
+        Gzip,
+        Deflate,
+    }
+
+    impl ContentEncoding {
+        /// Supported encodings in the order the server prefers them, if the client
+        /// accepts several with the same quality.
+        pub fn all() -> [ContentEncoding; 5] {
+            [
+                ContentEncoding::Brotli,
+                ContentEncoding::Zstd,
+                ContentEncoding::Gzip,
+                ContentEncoding::Deflate,
+                ContentEncoding::Identity,
+            ]
+        }
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                ContentEncoding::Identity => "identity",
+                ContentEncoding::Brotli => "br",
+                ContentEncoding::Zstd => "zstd",
+                ContentEncoding::Gzip => "gzip",
+                ContentEncoding::Deflate => "deflate",
+            }
+        }
+
+        /// Extension of a precompressed sibling file, `foo.js.br` for `foo.js`.
+        pub fn file_extension(&self) -> Option<&'static str> {
+            match self {
+                ContentEncoding::Brotli => Some("br"),
+                ContentEncoding::Zstd => Some("zst"),
+                ContentEncoding::Gzip => Some("gz"),
+                ContentEncoding::Identity | ContentEncoding::Deflate => None,
+            }
+        }
+    }
+
+    impl Display for ContentEncoding {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.name())
+        }
+    }
+
+    /// Picks the encoding with the highest quality value in the `Accept-Encoding` header.
+    /// Falls back to identity if the client accepts nothing the server supports.
+    pub fn negotiate(accept_encoding: Option<&str>) -> ContentEncoding {
+        accepted(accept_encoding).first().copied().unwrap_or(ContentEncoding::Identity)
+    }
+
+    /// The supported encodings the client accepts, best first. Codings without an explicit
+    /// entry get the quality of `*`, identity stays acceptable unless it is excluded with
+    /// a quality of 0.
+    pub fn accepted(accept_encoding: Option<&str>) -> Vec<ContentEncoding> {
+        let accept_encoding = match accept_encoding {
+            Some(accept_encoding) => accept_encoding,
+            None => return vec![ContentEncoding::Identity],
+        };
+        let preferences: Vec<(String, f32)> = accept_encoding
+            .split(',')
+            .filter_map(parse_preference)
+            .collect();
+        let quality = |name: &str| {
+            preferences
+                .iter()
+                .find(|(coding, _)| coding == name)
+                .or_else(|| preferences.iter().find(|(coding, _)| coding == "*"))
+                .map(|(_, q)| *q)
+        };
+        let mut accepted: Vec<(ContentEncoding, f32)> = ContentEncoding::all()
+            .into_iter()
+            .map(|encoding| match (quality(encoding.name()), encoding) {
+                (Some(q), _) => (encoding, q),
+                // Identity is always acceptable unless excluded explicitly.
+                (None, ContentEncoding::Identity) => (encoding, 0.001),
+                (None, _) => (encoding, 0.0),
+            })
+            .filter(|(_, q)| *q > 0.0)
+            .collect();
+        // The sort is stable, encodings with the same quality keep the server's order.
+        accepted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        accepted.into_iter().map(|(encoding, _)| encoding).collect()
+    }
+
+    /// Parses `coding;q=value`, entries with an invalid quality are ignored.
+    fn parse_preference(entry: &str) -> Option<(String, f32)> {
+        let mut parts = entry.split(';').map(|part| part.trim());
+        let coding = parts.next().filter(|coding| !coding.is_empty())?.to_lowercase();
+        let mut q = 1.0;
+        for parameter in parts {
+            if let Some((name, value)) = parameter.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    q = value.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+                }
+            }
+        }
+        Some((coding, q))
+    }
+}
@@ -1,22 +1,65 @@
 pub mod endpoint {
+    use crate::cache::cache::CachePolicy;
+    use crate::error_page::error_page::{ErrorPage, ErrorPages};
+    use crate::middleware::middleware::{Middleware, MiddlewareStack};
+    use crate::mime::mime::MimeTypes;
     use crate::path::path::remap;
-    use crate::resource::resource::ResourceHandler;
+    use crate::request_helper::request_helper::RequestArgs;
+    use crate::resource::resource::{ParameterError, ResourceHandler};
+    use crate::response::response::Response;
+    use crate::route::route::RouteTemplate;
+    use crate::router::router::{Router, RouterError};
+    use crate::status::status::{StatusClass, StatusCode};
     use crate::types::types::{HttpMethod, HttpRequest};
-    use std::collections::{HashMap, HashSet};
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fmt::{Display, Formatter};
     use std::fs;
     use std::path::{Path, PathBuf};
     use std::sync::Arc;
 
+    /// Name of the path argument holding the file path below an asset endpoint.
+    pub const ASSET_WILDCARD: &str = "asset";
+
     pub struct EndpointHandler {
         endpoints: Vec<Endpoint>,
+        router: Router,
         resource_handler: HashMap<String, Arc<ResourceHandler>>,
+        mime_types: MimeTypes,
+        error_pages: ErrorPages,
+        middleware: MiddlewareStack,
+    }
+
+    #[derive(Debug)]
+    pub enum RegistrationError {
+        Conflict { path: String, existing: String },
+        InvalidRoute(String),
+        InvalidLocation(String),
+    }
+
+    impl Display for RegistrationError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                RegistrationError::Conflict { path, existing } => {
+                    write!(f, "Path {} conflicts with existing endpoint {}.", path, existing)
+                }
+                RegistrationError::InvalidRoute(e) => write!(f, "Invalid route: {}", e),
+                RegistrationError::InvalidLocation(e) => write!(f, "Invalid location: {}", e),
+            }
+        }
     }
 
+    impl std::error::Error for RegistrationError {}
+
     impl EndpointHandler {
         pub fn create() -> EndpointHandler {
             return EndpointHandler {
                 endpoints: vec![],
+                router: Router::create(),
                 resource_handler: HashMap::new(),
+                mime_types: MimeTypes::create(),
+                error_pages: ErrorPages::create(),
+                middleware: MiddlewareStack::create(),
             };
         }
 
@@ -27,12 +70,47 @@ pub mod endpoint {
                 .map(|(key, val)| (key.clone(), Arc::clone(val)))
                 .collect();
             return EndpointProvider {
-                endpoints: self.endpoints.to_vec(), // performance for many threads?
+                endpoints: self.endpoints.to_vec(),
+                router: self.router.clone(),
                 resource_handler: resource_handler_copy,
+                mime_types: self.mime_types.clone(),
+                error_pages: Arc::new(self.error_pages.clone().with_mime_types(self.mime_types.clone())),
+                middleware: self.middleware.clone(),
             };
         }
 
-        pub fn register_assets(&mut self, location: String, mapping: String) {
+        /// Sets the `Content-Type` served for files with the extension, replacing the default.
+        pub fn register_mime_type(&mut self, extension: &str, mime_type: &str) {
+            self.mime_types.insert(extension, mime_type);
+        }
+
+        /// Body of the error responses with the status, e.g. a `404.html` file.
+        pub fn register_error_page(&mut self, status: StatusCode, page: ErrorPage) {
+            self.error_pages.insert(status, page);
+        }
+
+        /// Body of the error responses in the class without a page for their exact status.
+        pub fn register_error_class_page(&mut self, class: StatusClass, page: ErrorPage) {
+            self.error_pages.insert_class(class, page);
+        }
+
+        /// Middleware wrapping the dispatch of every request. Middleware runs in the order
+        /// it is registered, the first registered sees the request first and the response last.
+        pub fn register_middleware(&mut self, middleware: Box<dyn Middleware>) {
+            self.middleware.push("/", Arc::from(middleware));
+        }
+
+        /// Like `register_middleware`, but only for requests to the prefix and the paths below it.
+        pub fn register_middleware_for(&mut self, prefix: String, middleware: Box<dyn Middleware>) {
+            self.middleware.push(&prefix, Arc::from(middleware));
+        }
+
+        pub fn register_assets(&mut self, location: String, mapping: String) -> Result<(), RegistrationError> {
+            self.register_assets_with_cache(location, mapping, CachePolicy::default())
+        }
+
+        /// Like `register_assets`, the files are served with the `Cache-Control` of the policy.
+        pub fn register_assets_with_cache(&mut self, location: String, mapping: String, cache_policy: CachePolicy) -> Result<(), RegistrationError> {
             let absolute_path = self
                 .map_to_absolute(&location)
                 .into_os_string()
@@ -49,33 +127,27 @@ pub mod endpoint {
                 endpoint_type: EndpointType::Assets(AssetEndpoint {
                     asset_base: absolute_path,
                 }),
+                cache_policy,
             };
-            self.register_endpoint(endpoint);
+            self.register_endpoint(endpoint)
         }
 
-        pub fn register_static(&mut self, location: String, mapping: String) {
+        pub fn register_static(&mut self, location: String, mapping: String) -> Result<(), RegistrationError> {
+            self.register_static_with_cache(location, mapping, CachePolicy::default())
+        }
+
+        /// Like `register_static`, the files are served with the `Cache-Control` of the policy.
+        pub fn register_static_with_cache(&mut self, location: String, mapping: String, cache_policy: CachePolicy) -> Result<(), RegistrationError> {
             let absolute_path = self.map_to_absolute(&location);
-            for local_asset_path_res in fs::read_dir(absolute_path).unwrap() {
+            let local_asset_paths = fs::read_dir(&absolute_path)
+                .map_err(|e| RegistrationError::InvalidLocation(format!("{:?}: {}", absolute_path, e)))?;
+            for local_asset_path_res in local_asset_paths {
                 match local_asset_path_res {
                     Ok(local_asset_path) => {
-                        let asset_path_str = local_asset_path
-                            .path()
-                            .into_os_string()
-                            .into_string()
-                            .unwrap();
-                        if let Some(existing) =
-                            self.endpoints.iter().find(|e| e.path == asset_path_str)
-                        {
-                            println!(
-                                "Path {} already registered: {:?}. Skip.",
-                                asset_path_str, existing
-                            );
-                            continue;
-                        }
                         let full_asset_path = local_asset_path.path();
                         let directory = full_asset_path.parent().unwrap();
                         let remapped_path =
-                            remap(&full_asset_path, &directory, Path::new(&mapping));
+                            remap(&full_asset_path, directory, Path::new(&mapping));
                         let remapped_path_str = remapped_path
                             .as_os_str()
                             .to_os_string()
@@ -94,67 +166,92 @@ pub mod endpoint {
                                 remapped_path_str,
                                 full_asset_path.into_os_string().into_string().unwrap(),
                                 vec![alias_path],
+                                cache_policy.clone(),
                             );
-                            self.register_endpoint(endpoint);
+                            self.register_endpoint(endpoint)?;
                         } else {
                             let endpoint = Endpoint::asset(
-                                String::from(remapped_path_str),
+                                remapped_path_str,
                                 full_asset_path.into_os_string().into_string().unwrap(),
                                 vec![],
+                                cache_policy.clone(),
                             );
-                            self.register_endpoint(endpoint);
+                            self.register_endpoint(endpoint)?;
                         }
                     }
                     Err(_) => continue,
                 }
             }
+            Ok(())
         }
 
+        /// Registers the handler for the method and path. Every handler needs its own id,
+        /// registering another method for the same path takes a second id.
         pub fn register_resource(
             &mut self,
+            method: HttpMethod,
             mapping: String,
             handler_id: String,
             handler: Box<ResourceHandler>,
-        ) {
+        ) -> Result<(), RegistrationError> {
             let mapping_corrected = match mapping.starts_with("/") {
                 true => mapping,
                 false => ["/", &mapping].join(""),
             };
+            // Handlers are looked up by id, a second handler with the same id would replace the first.
+            if let Some(existing) = self.endpoints.iter().find(|e| match &e.endpoint_type {
+                EndpointType::Resource(r) => r.resource_handler_id == handler_id,
+                _ => false,
+            }) {
+                return Err(RegistrationError::Conflict {
+                    path: mapping_corrected,
+                    existing: existing.path.clone(),
+                });
+            }
+            let route = RouteTemplate::parse(&mapping_corrected).map_err(RegistrationError::InvalidRoute)?;
             let endpoint = Endpoint {
                 endpoint_type: EndpointType::Resource(ResourceEndpoint {
                     resource_handler_id: handler_id.clone(),
+                    route,
                 }),
                 path: mapping_corrected,
                 aliases: vec![],
-                methods: vec![HttpMethod::Get],
+                methods: vec![method],
+                cache_policy: CachePolicy::default(),
             };
+            self.register_endpoint(endpoint)?;
             self.resource_handler
                 .insert(handler_id.clone(), Arc::from(handler));
-            self.register_endpoint(endpoint);
+            Ok(())
         }
 
-        fn register_endpoint(&mut self, endpoint: Endpoint) {
-            if self.conflicts_existing(&endpoint) {
-                return;
+        fn register_endpoint(&mut self, endpoint: Endpoint) -> Result<(), RegistrationError> {
+            let routes = endpoint.routes();
+            for route in &routes {
+                let endpoints = &self.endpoints;
+                let conflicts = |existing: usize| {
+                    endpoints[existing].methods.iter().any(|m| endpoint.methods.contains(m))
+                };
+                if let Err(e) = self.router.check(route, conflicts) {
+                    return Err(match e {
+                        RouterError::Conflict(existing) => RegistrationError::Conflict {
+                            path: endpoint.path.clone(),
+                            existing: endpoints[existing].path.clone(),
+                        },
+                        RouterError::ParameterMismatch(name) => RegistrationError::InvalidRoute(format!(
+                            "{} uses a different parameter name than the existing {}",
+                            endpoint.path, name
+                        )),
+                    });
+                }
+            }
+            let index = self.endpoints.len();
+            for route in &routes {
+                self.router.insert(route, index);
             }
             println!("Registered endpoint: {:?}", endpoint);
             self.endpoints.push(endpoint);
-        }
-
-        fn conflicts_existing(&self, endpoint: &Endpoint) -> bool {
-            let existing_paths: HashSet<&String> =
-                HashSet::from_iter(self.endpoints.iter().flat_map(|e| e.get_all_paths()));
-            let endpoint_paths: HashSet<&String> = endpoint.get_all_paths();
-
-            let conflicting_endpoints = existing_paths.intersection(&endpoint_paths);
-            if conflicting_endpoints.count() > 0 {
-                println!(
-                    "{:?} conflicts with existing paths: {:?}",
-                    endpoint, endpoint_paths
-                );
-                return true;
-            }
-            return false;
+            Ok(())
         }
 
         fn map_to_absolute(&self, location: &String) -> PathBuf {
@@ -164,7 +261,7 @@ pub mod endpoint {
                 false => {
                     let cleaned_location = match location.starts_with("./") {
                         true => &location[2..],
-                        false => &location,
+                        false => location,
                     };
                     let current_dir = std::env::current_dir().unwrap();
                     Path::new(&current_dir).join(cleaned_location)
@@ -175,32 +272,80 @@ pub mod endpoint {
 
     pub struct EndpointProvider {
         endpoints: Vec<Endpoint>,
+        router: Router,
         resource_handler: HashMap<String, Arc<ResourceHandler>>,
+        mime_types: MimeTypes,
+        error_pages: Arc<ErrorPages>,
+        middleware: MiddlewareStack,
     }
 
     impl EndpointProvider {
-        pub fn match_endpoint(&self, path: String, method: HttpMethod) -> Option<&Endpoint> {
+        pub fn match_endpoint<'a>(&'a self, path: &'a str, method: HttpMethod) -> EndpointMatch<'a> {
             println!(
                 "Called to resolve endpoint for path {} with method {:?}",
                 path, method
             );
-            return self.endpoints.iter().find(|e| match &e.endpoint_type {
-                EndpointType::Assets(_) => {
-                    return path.starts_with(&e.path);
-                }
-                _ => (e.path == path || e.aliases.contains(&path)) && e.methods.contains(&method),
-            });
+            let (indices, args) = match self.router.lookup(path) {
+                Some(res) => res,
+                None => return EndpointMatch::NotFound,
+            };
+            let candidates: Vec<&Endpoint> = indices.iter().map(|i| &self.endpoints[*i]).collect();
+            let lookup_method = match method {
+                HttpMethod::Head => HttpMethod::Get,
+                _ => method,
+            };
+            if let Some(endpoint) = candidates.iter().find(|e| e.methods.contains(&lookup_method)) {
+                return EndpointMatch::Found(endpoint, args);
+            }
+            let registered: Vec<HttpMethod> = candidates
+                .iter()
+                .flat_map(|e| e.methods.iter().cloned())
+                .collect();
+            EndpointMatch::MethodNotAllowed(allowed_methods(&registered))
         }
-        pub fn execute(&self, r: &ResourceEndpoint, request: &HttpRequest) -> String {
+
+        pub fn execute(&self, r: &ResourceEndpoint, request: &HttpRequest, path_args: &[RequestArgs]) -> Result<Response, Vec<ParameterError>> {
             let handler = self.resource_handler.get(&r.resource_handler_id).unwrap();
-            return handler.handle(request);
+            return handler.handle(request, path_args);
+        }
+
+        pub fn mime_type(&self, file_path: &str) -> &str {
+            self.mime_types.lookup(Path::new(file_path))
         }
+
+        pub fn error_pages(&self) -> Arc<ErrorPages> {
+            Arc::clone(&self.error_pages)
+        }
+
+        pub fn middleware_for(&self, path: &str) -> Vec<Arc<dyn Middleware>> {
+            self.middleware.for_path(path)
+        }
+    }
+
+    pub enum EndpointMatch<'a> {
+        Found(&'a Endpoint, Vec<RequestArgs<'a>>),
+        MethodNotAllowed(Vec<HttpMethod>),
+        NotFound,
+    }
+
+    /// Derives the methods to announce in an `Allow` header from the registered ones,
+    /// HEAD is served for every GET endpoint and OPTIONS is always answered.
+    pub fn allowed_methods(registered: &[HttpMethod]) -> Vec<HttpMethod> {
+        HttpMethod::all()
+            .into_iter()
+            .filter(|m| match m {
+                HttpMethod::Head => registered.contains(&HttpMethod::Get) || registered.contains(m),
+                HttpMethod::Options => true,
+                _ => registered.contains(m),
+            })
+            .collect()
     }
 
     #[derive(Debug, Clone)]
     pub struct Endpoint {
         pub path: String,
         pub endpoint_type: EndpointType,
+        pub cache_policy: CachePolicy,
         aliases: Vec<String>,
         methods: Vec<HttpMethod>,
     }
@@ -218,10 +363,11 @@ pub mod endpoint {
     #[derive(Debug, Clone)]
     pub struct ResourceEndpoint {
         pub resource_handler_id: String,
+        pub route: RouteTemplate,
     }
 
     impl Endpoint {
-        pub fn asset(path: String, asset_base_path: String, aliases: Vec<String>) -> Endpoint {
+        pub fn asset(path: String, asset_base_path: String, aliases: Vec<String>, cache_policy: CachePolicy) -> Endpoint {
             return Endpoint {
                 endpoint_type: EndpointType::StaticAsset(StaticEndpoint {
                     asset_path: asset_base_path,
@@ -229,14 +375,20 @@ pub mod endpoint {
                 path,
                 aliases,
                 methods: vec![HttpMethod::Get],
+                cache_policy,
             };
         }
 
-        pub fn get_all_paths(&self) -> HashSet<&String> {
-            let mut all_paths = self.aliases.iter().collect::<HashSet<&String>>();
-            let path_set: HashSet<&String> = [&self.path].iter().cloned().collect();
-            all_paths.extend(path_set);
-            all_paths
+        fn routes(&self) -> Vec<RouteTemplate> {
+            match &self.endpoint_type {
+                EndpointType::Resource(r) => vec![r.route.clone()],
+                EndpointType::Assets(_) => vec![RouteTemplate::prefix(&self.path, ASSET_WILDCARD)],
+                EndpointType::StaticAsset(_) => [&self.path]
+                    .into_iter()
+                    .chain(self.aliases.iter())
+                    .map(|p| RouteTemplate::literal(p))
+                    .collect(),
+            }
         }
     }
 
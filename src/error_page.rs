@@ -0,0 +1,106 @@
+pub mod error_page {
+    use crate::mime::mime::MimeTypes;
+    use crate::negotiation::negotiation::negotiate_media_type;
+    use crate::problem::problem::{Problem, PROBLEM_JSON};
+    use crate::response::response::{Response, ResponseBody};
+    use crate::status::status::{StatusClass, StatusCode};
+    use crate::template_engine::template_engine::TemplateEngine;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    /// Body of an error response.
+    #[derive(Debug, Clone)]
+    pub enum ErrorPage {
+        /// Sent as it is, the `Content-Type` follows the file extension.
+        File(PathBuf),
+        /// Html rendered with the `TemplateEngine`, `${status}` and `${reason}` are replaced
+        /// with the status code and its reason phrase.
+        Template(String),
+    }
+
+    /// Error pages by status code and status class, a page for the exact code wins.
+    #[derive(Debug, Clone)]
+    pub struct ErrorPages {
+        by_status: HashMap<StatusCode, ErrorPage>,
+        by_class: HashMap<StatusClass, ErrorPage>,
+        mime_types: MimeTypes,
+    }
+
+    impl ErrorPages {
+        pub fn create() -> ErrorPages {
+            ErrorPages {
+                by_status: HashMap::new(),
+                by_class: HashMap::new(),
+                mime_types: MimeTypes::create(),
+            }
+        }
+
+        pub fn insert(&mut self, status: StatusCode, page: ErrorPage) {
+            self.by_status.insert(status, page);
+        }
+
+        pub fn insert_class(&mut self, class: StatusClass, page: ErrorPage) {
+            self.by_class.insert(class, page);
+        }
+
+        /// Types used for the `Content-Type` of file pages.
+        pub fn with_mime_types(mut self, mime_types: MimeTypes) -> ErrorPages {
+            self.mime_types = mime_types;
+            self
+        }
+
+        /// Fills the body of an error response without content. A registered page is used
+        /// unless the client prefers another representation, otherwise the body is generated
+        /// as problem details in the format negotiated with the `Accept` header.
+        pub fn apply(&self, response: Response, accept: Option<&str>) -> Response {
+            let status = response.get_status();
+            if !status.is_error() || !matches!(response.get_body(), ResponseBody::Empty) {
+                return response;
+            }
+            let page = match self.by_status.get(&status).or_else(|| self.by_class.get(&status.class())) {
+                Some(ErrorPage::File(path)) if !path.is_file() => {
+                    println!("Error page {:?} for {} does not exist", path, status);
+                    None
+                }
+                page => page,
+            };
+            if let Some(page) = page {
+                let content_type = match page {
+                    ErrorPage::File(path) => self.mime_types.lookup(path),
+                    ErrorPage::Template(_) => "text/html; charset=utf-8",
+                };
+                let available = [content_type, PROBLEM_JSON, "application/json", "text/plain"];
+                if matches!(negotiate_media_type(accept, &available), Some(selected) if selected == content_type) {
+                    return response
+                        .header("Content-Type", content_type)
+                        .header("Vary", "Accept")
+                        .body(self.render(page, status));
+                }
+            }
+            let (content_type, body) = Problem::create(status).render(accept);
+            response
+                .header("Content-Type", content_type)
+                .header("Vary", "Accept")
+                .body(ResponseBody::Text(body))
+        }
+
+        fn render(&self, page: &ErrorPage, status: StatusCode) -> ResponseBody {
+            match page {
+                ErrorPage::File(path) => ResponseBody::File(path.clone()),
+                ErrorPage::Template(template) => {
+                    let context = HashMap::from([
+                        (String::from("status"), status.code().to_string()),
+                        (String::from("reason"), String::from(status.reason_phrase())),
+                    ]);
+                    ResponseBody::Text(TemplateEngine {}.render(template, context))
+                }
+            }
+        }
+    }
+
+    impl Default for ErrorPages {
+        fn default() -> ErrorPages {
+            ErrorPages::create()
+        }
+    }
+}
@@ -1,19 +1,21 @@
 pub mod file {
-    use std::fs;
+    use std::fs::{File, Metadata};
     use std::path::Path;
 
-    pub fn read_file(file_path: &String) -> Result<String, String> {
+    /// Opens the file for streaming and returns it together with its metadata.
+    pub fn open_file(file_path: &str) -> Result<(File, Metadata), String> {
         let path = Path::new(file_path);
-        if !path.exists() {
-            let error = String::from("File does not exist: ") + path.to_str().expect("");
+        if !path.is_file() {
+            let error = String::from("File does not exist: ") + file_path;
             println!("{}", error);
             return Err(String::from("File does not exist"));
         }
-        return match fs::read_to_string(path) {
-            Ok(content) => Ok(content),
+        let file = File::open(path).map_err(|e| format!("Failed to open file {}: {}", file_path, e))?;
+        return match file.metadata() {
+            Ok(metadata) => Ok((file, metadata)),
             Err(e) => {
                 println!("{}", e);
-                let error = String::from("Failed to read file: ") + &file_path;
+                let error = String::from("Failed to read file: ") + file_path;
                 return Err(error);
             }
         };
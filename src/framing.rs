@@ -0,0 +1,54 @@
+pub mod framing {
+    use crate::body::body::{find_header_end, read_body, BodyError, BodyLength};
+    use crate::parser::parser::{ParseError, RequestLimits};
+
+    /// Splits the bytes received on a connection into requests. Data read past the end of
+    /// a request stays buffered, so pipelined requests are taken off one after another.
+    pub struct RequestFramer {
+        buffer: Vec<u8>,
+        limits: RequestLimits,
+    }
+
+    impl RequestFramer {
+        pub fn create(limits: RequestLimits) -> RequestFramer {
+            RequestFramer { buffer: vec![], limits }
+        }
+
+        pub fn push(&mut self, data: &[u8]) {
+            self.buffer.extend_from_slice(data);
+        }
+
+        /// Whether no part of the next request has been received yet.
+        pub fn is_empty(&self) -> bool {
+            self.buffer.is_empty()
+        }
+
+        /// Takes the head of the next request off the buffer, without the empty line ending it.
+        /// Returns `None` if more data is needed.
+        pub fn next_head(&mut self) -> Result<Option<Vec<u8>>, ParseError> {
+            // Empty lines before a request line are ignored, RFC 9112 section 2.2.
+            let leading_lines = self.buffer.chunks(2).take_while(|it| *it == b"\r\n").count();
+            self.buffer.drain(..leading_lines * 2);
+            match find_header_end(&self.buffer) {
+                Some(end) => {
+                    let head = self.buffer[..end].to_vec();
+                    self.buffer.drain(..end + 4);
+                    Ok(Some(head))
+                }
+                None => self.limits.check_incomplete_head(&self.buffer).map(|_| None),
+            }
+        }
+
+        /// Takes the body following the head off the buffer. Returns `None` if more data is needed,
+        /// the progress of a chunked body is kept in `body_length` for the next call.
+        pub fn next_body(&mut self, body_length: &mut BodyLength) -> Result<Option<Vec<u8>>, BodyError> {
+            match read_body(body_length, &self.buffer, self.limits.max_body_size)? {
+                Some((body, consumed)) => {
+                    self.buffer.drain(..consumed);
+                    Ok(Some(body))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
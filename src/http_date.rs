@@ -0,0 +1,96 @@
+pub mod http_date {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    /// Latest year a parsed date may have, later years are not valid http dates.
+    const MAX_YEAR: i64 = 9999;
+
+    /// Formats the time as IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+    pub fn format_http_date(time: SystemTime) -> String {
+        let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let days = (seconds / 86400) as i64;
+        let (year, month, day) = civil_from_days(days);
+        let second_of_day = seconds % 86400;
+        format!(
+            "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+            // 1970-01-01 was a thursday.
+            DAYS[((days + 3) % 7) as usize],
+            day,
+            MONTHS[(month - 1) as usize],
+            year,
+            second_of_day / 3600,
+            second_of_day / 60 % 60,
+            second_of_day % 60
+        )
+    }
+
+    /// Parses the three date formats recipients have to accept: IMF-fixdate, the obsolete
+    /// RFC 850 format and the asctime format.
+    pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        let (day, month, year, time) = match parts.as_slice() {
+            // Sun, 06 Nov 1994 08:49:37 GMT
+            [_, day, month, year, time, "GMT"] => (day.parse().ok()?, *month, year.parse().ok()?, *time),
+            // Sunday, 06-Nov-94 08:49:37 GMT
+            [_, date, time, "GMT"] => {
+                let date: Vec<&str> = date.split('-').collect();
+                match date.as_slice() {
+                    [day, month, year] if year.len() == 2 => {
+                        let year: i64 = year.parse().ok()?;
+                        // Http dates cannot lie before the epoch, so 70 to 99 belong to the last century.
+                        let year = if year < 70 { 2000 + year } else { 1900 + year };
+                        (day.parse().ok()?, *month, year, *time)
+                    }
+                    _ => return None,
+                }
+            }
+            // Sun Nov  6 08:49:37 1994
+            [_, month, day, time, year] => (day.parse().ok()?, *month, year.parse().ok()?, *time),
+            _ => return None,
+        };
+        let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+        let time: Vec<u64> = time.split(':').map(|t| t.parse().ok()).collect::<Option<Vec<u64>>>()?;
+        let (hour, minute, second) = match time.as_slice() {
+            [hour, minute, second] if *hour < 24 && *minute < 60 && *second < 61 => (*hour, *minute, *second),
+            _ => return None,
+        };
+        // The four digit years of the formats keep the arithmetic below far from overflowing.
+        if !(1..=31).contains(&day) || !(1970..=MAX_YEAR).contains(&year) {
+            return None;
+        }
+        let days = days_from_civil(year, month, day) as u64;
+        let seconds = days.checked_mul(86400)?.checked_add(hour * 3600 + minute * 60 + second)?;
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+    }
+
+    /// Truncates the time to whole seconds, the precision of http dates.
+    pub fn truncate_to_seconds(time: SystemTime) -> SystemTime {
+        let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    // Conversions between days since the epoch and the proleptic gregorian calendar,
+    // see http://howardhinnant.github.io/date_algorithms.html
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    fn civil_from_days(days: i64) -> (i64, i64, i64) {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+}
@@ -0,0 +1,51 @@
+//! Multithreaded http server with support for static assets, resource endpoints
+//! and a simple template engine.
+
+#![allow(clippy::module_inception, clippy::needless_return)]
+
+mod body;
+mod cache;
+mod compression;
+mod encoding;
+mod endpoint;
+mod error_page;
+mod file;
+mod framing;
+mod http_date;
+mod middleware;
+mod mime;
+mod negotiation;
+mod parser;
+mod path;
+mod problem;
+mod range;
+mod request_helper;
+mod resource;
+mod response;
+mod route;
+mod router;
+mod shutdown;
+mod status;
+mod threads;
+mod types;
+mod validation;
+mod web_server;
+mod template_engine;
+
+pub use crate::cache::cache::CachePolicy;
+pub use crate::compression::compression::{CompressionLevel, CompressionPolicy};
+pub use crate::endpoint::endpoint::{EndpointHandler, RegistrationError};
+pub use crate::error_page::error_page::ErrorPage;
+pub use crate::middleware::middleware::{Middleware, Next};
+pub use crate::negotiation::negotiation::{negotiate_media_type, parse_accept, MediaRange};
+pub use crate::problem::problem::Problem;
+pub use crate::request_helper::request_helper::{RequestArgValue, RequestArgs};
+pub use crate::resource::resource::{
+    ParameterError, ResourceHandler, ResourceParameter, ResourceParameterLocation, ResourceParameterType,
+};
+pub use crate::response::response::{Cookie, Response, ResponseBody};
+pub use crate::shutdown::shutdown::ShutdownHandle;
+pub use crate::status::status::{StatusClass, StatusCode};
+pub use crate::template_engine::template_engine::TemplateEngine;
+pub use crate::types::types::{GeneralRequest, HttpMethod, HttpRequest, HttpVersion};
+pub use crate::web_server::web_server::{WebServer, WebServerBuilder};
@@ -0,0 +1,79 @@
+pub mod middleware {
+    use crate::path::path::normalized_segments;
+    use crate::response::response::Response;
+    use crate::route::route::split_segments;
+    use crate::types::types::HttpRequest;
+    use std::sync::Arc;
+
+    /// Wraps the dispatch of a request. A middleware may inspect or modify the request
+    /// before calling `next`, answer it itself without calling `next`, and post-process
+    /// the response `next` returns.
+    pub trait Middleware: Send + Sync {
+        fn handle(&self, request: &mut HttpRequest, next: Next) -> Response;
+    }
+
+    impl<F> Middleware for F
+    where
+        F: Fn(&mut HttpRequest, Next) -> Response + Send + Sync,
+    {
+        fn handle(&self, request: &mut HttpRequest, next: Next) -> Response {
+            self(request, next)
+        }
+    }
+
+    /// The rest of the chain, ending with the endpoint the request is routed to.
+    pub struct Next<'a> {
+        chain: &'a [Arc<dyn Middleware>],
+        endpoint: &'a dyn Fn(&HttpRequest) -> Response,
+    }
+
+    impl<'a> Next<'a> {
+        pub fn create(chain: &'a [Arc<dyn Middleware>], endpoint: &'a dyn Fn(&HttpRequest) -> Response) -> Next<'a> {
+            Next { chain, endpoint }
+        }
+
+        pub fn run(self, request: &mut HttpRequest) -> Response {
+            match self.chain.split_first() {
+                Some((middleware, rest)) => middleware.handle(request, Next::create(rest, self.endpoint)),
+                None => (self.endpoint)(request),
+            }
+        }
+    }
+
+    /// Registered middleware in registration order, each either global or limited to
+    /// the paths below a prefix.
+    #[derive(Clone, Default)]
+    pub struct MiddlewareStack {
+        entries: Vec<(Vec<String>, Arc<dyn Middleware>)>,
+    }
+
+    impl MiddlewareStack {
+        pub fn create() -> MiddlewareStack {
+            MiddlewareStack { entries: vec![] }
+        }
+
+        /// The prefix is matched by whole segments, `/api` applies to `/api` and `/api/users`
+        /// but not to `/apis`. An empty prefix or `/` applies to every request.
+        pub fn push(&mut self, prefix: &str, middleware: Arc<dyn Middleware>) {
+            let segments = split_segments(prefix).into_iter().map(|(_, segment)| String::from(segment)).collect();
+            self.entries.push((segments, middleware));
+        }
+
+        /// The middleware applying to the path, outermost first. The prefix is compared with
+        /// the segments as the router splits them and with the decoded, normalized segments
+        /// asset endpoints resolve, so `/api/./x` or `/%61pi/x` cannot bypass middleware on `/api`.
+        pub fn for_path(&self, path: &str) -> Vec<Arc<dyn Middleware>> {
+            let segments: Vec<&str> = split_segments(path).into_iter().map(|(_, segment)| segment).collect();
+            let normalized = normalized_segments(path).unwrap_or_default();
+            self.entries
+                .iter()
+                .filter(|(prefix, _)| starts_with(&segments, prefix) || starts_with(&normalized, prefix))
+                .map(|(_, middleware)| Arc::clone(middleware))
+                .collect()
+        }
+    }
+
+    fn starts_with<S: AsRef<str>>(segments: &[S], prefix: &[String]) -> bool {
+        prefix.len() <= segments.len() && prefix.iter().zip(segments).all(|(expected, segment)| expected == segment.as_ref())
+    }
+}
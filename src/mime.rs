@@ -0,0 +1,81 @@
+pub mod mime {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+    /// Maps file extensions to the `Content-Type` of served files. Extensions are
+    /// matched case-insensitively and without the leading dot.
+    #[derive(Debug, Clone)]
+    pub struct MimeTypes {
+        types: HashMap<String, String>,
+    }
+
+    impl MimeTypes {
+        /// Table with the types commonly found on a website.
+        pub fn create() -> MimeTypes {
+            let types = [
+                ("html", "text/html; charset=utf-8"),
+                ("htm", "text/html; charset=utf-8"),
+                ("css", "text/css; charset=utf-8"),
+                ("js", "text/javascript; charset=utf-8"),
+                ("mjs", "text/javascript; charset=utf-8"),
+                ("json", "application/json"),
+                ("map", "application/json"),
+                ("xml", "application/xml"),
+                ("txt", "text/plain; charset=utf-8"),
+                ("csv", "text/csv; charset=utf-8"),
+                ("md", "text/markdown; charset=utf-8"),
+                ("svg", "image/svg+xml"),
+                ("png", "image/png"),
+                ("jpg", "image/jpeg"),
+                ("jpeg", "image/jpeg"),
+                ("gif", "image/gif"),
+                ("webp", "image/webp"),
+                ("avif", "image/avif"),
+                ("bmp", "image/bmp"),
+                ("ico", "image/vnd.microsoft.icon"),
+                ("woff", "font/woff"),
+                ("woff2", "font/woff2"),
+                ("ttf", "font/ttf"),
+                ("otf", "font/otf"),
+                ("eot", "application/vnd.ms-fontobject"),
+                ("pdf", "application/pdf"),
+                ("zip", "application/zip"),
+                ("gz", "application/gzip"),
+                ("tar", "application/x-tar"),
+                ("wasm", "application/wasm"),
+                ("mp3", "audio/mpeg"),
+                ("ogg", "audio/ogg"),
+                ("wav", "audio/wav"),
+                ("mp4", "video/mp4"),
+                ("webm", "video/webm"),
+            ]
+            .into_iter()
+            .map(|(extension, mime_type)| (String::from(extension), String::from(mime_type)))
+            .collect();
+            MimeTypes { types }
+        }
+
+        /// Adds a mapping or replaces the existing one for the extension.
+        pub fn insert(&mut self, extension: &str, mime_type: &str) {
+            let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+            self.types.insert(extension, String::from(mime_type));
+        }
+
+        /// Type of the file at the path, `application/octet-stream` for unknown extensions.
+        pub fn lookup(&self, path: &Path) -> &str {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(|extension| self.types.get(&extension.to_ascii_lowercase()))
+                .map(|mime_type| mime_type.as_str())
+                .unwrap_or(DEFAULT_MIME_TYPE)
+        }
+    }
+
+    impl Default for MimeTypes {
+        fn default() -> MimeTypes {
+            MimeTypes::create()
+        }
+    }
+}
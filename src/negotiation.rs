@@ -0,0 +1,75 @@
+pub mod negotiation {
+
+    /// Entry of an `Accept` header like `text/*;q=0.5`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct MediaRange {
+        pub media_type: String,
+        pub subtype: String,
+        pub q: f32,
+    }
+
+    impl MediaRange {
+        /// Specificity of the range if it matches the type, `*/*` < `text/*` < `text/html`.
+        fn matches(&self, media_type: &str, subtype: &str) -> Option<u8> {
+            match (self.media_type.as_str(), self.subtype.as_str()) {
+                ("*", "*") => Some(0),
+                (t, "*") if t == media_type => Some(1),
+                (t, s) if t == media_type && s == subtype => Some(2),
+                _ => None,
+            }
+        }
+    }
+
+    /// Parses an `Accept` header, entries that are not a valid media range or have an
+    /// invalid quality are ignored.
+    pub fn parse_accept(accept: &str) -> Vec<MediaRange> {
+        accept.split(',').filter_map(parse_media_range).collect()
+    }
+
+    /// Picks the type the client prefers among the available ones, following RFC 9110
+    /// section 12.5.1. The most specific range decides the quality of a type, types with
+    /// the same quality keep the order of `available`. Without an `Accept` header the
+    /// first type is used, `None` means the client accepts none of them.
+    pub fn negotiate_media_type<'a>(accept: Option<&str>, available: &[&'a str]) -> Option<&'a str> {
+        let ranges = match accept.map(str::trim) {
+            Some(accept) if !accept.is_empty() => parse_accept(accept),
+            _ => return available.first().copied(),
+        };
+        let mut selected: Option<(&'a str, f32)> = None;
+        for candidate in available {
+            let essence = candidate.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+            let (media_type, subtype) = match essence.split_once('/') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let q = ranges
+                .iter()
+                .filter_map(|range| range.matches(media_type, subtype).map(|specificity| (specificity, range.q)))
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, q)| q)
+                .unwrap_or(0.0);
+            if q > 0.0 && selected.is_none_or(|(_, best)| q > best) {
+                selected = Some((candidate, q));
+            }
+        }
+        selected.map(|(candidate, _)| candidate)
+    }
+
+    fn parse_media_range(entry: &str) -> Option<MediaRange> {
+        let mut parts = entry.split(';').map(|part| part.trim());
+        let (media_type, subtype) = parts.next()?.split_once('/')?;
+        let (media_type, subtype) = (media_type.trim().to_ascii_lowercase(), subtype.trim().to_ascii_lowercase());
+        if media_type.is_empty() || subtype.is_empty() || (media_type == "*" && subtype != "*") {
+            return None;
+        }
+        let mut q = 1.0;
+        for parameter in parts {
+            if let Some((name, value)) = parameter.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    q = value.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+                }
+            }
+        }
+        Some(MediaRange { media_type, subtype, q })
+    }
+}
@@ -1,82 +1,446 @@
 pub mod parser {
     use crate::request_helper::request_helper::{clean_path, get_parameters_from_path};
+    use crate::status::status::StatusCode;
     use crate::types::types::{GeneralRequest, HttpMethod, HttpRequest, HttpVersion};
     use std::collections::HashMap;
+    use std::fmt;
+    use std::fmt::Formatter;
 
-    pub fn parse(request: &str) -> Result<HttpRequest, &str> {
-        let request_split: Vec<&str> = request.split("\r\n").collect();
-        match request_split.as_slice() {
-            [] => Err("Empty request"),
-            [general_line, headers @ ..] => {
-                return match parse_general(general_line) {
-                    Ok(general) => Ok(HttpRequest {
-                        general,
-                        headers: parse_headers(headers),
-                    }),
-                    Err(_) => Err("Failed to parse request"),
+    /// Size limits of a request, the body limit is enforced by the connection reader.
+    #[derive(Debug, Copy, Clone)]
+    pub struct RequestLimits {
+        pub max_request_line: usize,
+        /// Bytes of all header fields together, including their line endings.
+        pub max_header_size: usize,
+        pub max_headers: usize,
+        pub max_body_size: usize,
+    }
+
+    impl Default for RequestLimits {
+        fn default() -> RequestLimits {
+            RequestLimits {
+                max_request_line: 8 * 1024,
+                max_header_size: 16 * 1024,
+                max_headers: 100,
+                max_body_size: 10 * 1024 * 1024,
+            }
+        }
+    }
+
+    impl RequestLimits {
+        /// Checks the start of a request whose head is not complete yet, so a client can not
+        /// make the server buffer data until the empty line arrives.
+        pub fn check_incomplete_head(&self, received: &[u8]) -> Result<(), ParseError> {
+            let request_line_end = received.windows(2).position(|it| it == b"\r\n");
+            match request_line_end {
+                None if received.len() > self.max_request_line => Err(ParseError::RequestLineTooLong),
+                Some(end) if received.len() - end > self.max_header_size + 4 => Err(ParseError::HeadersTooLarge),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum ParseError {
+        EmptyRequest,
+        InvalidRequestLine,
+        InvalidMethod,
+        /// A syntactically valid method the server does not implement.
+        UnknownMethod(String),
+        InvalidTarget,
+        InvalidVersion,
+        UnsupportedVersion(String),
+        RequestLineTooLong,
+        InvalidField(String),
+        /// A line is terminated by a bare LF instead of CRLF.
+        InvalidLineEnding,
+        /// Field values continued on the next line, deprecated by RFC 9112 section 5.2.
+        ObsoleteLineFolding,
+        HeadersTooLarge,
+        TooManyFields,
+        MissingHost,
+    }
+
+    impl ParseError {
+        /// Status code of the response rejecting the request.
+        pub fn status(&self) -> StatusCode {
+            match self {
+                ParseError::UnknownMethod(_) => StatusCode::NOT_IMPLEMENTED,
+                ParseError::RequestLineTooLong => StatusCode::URI_TOO_LONG,
+                ParseError::HeadersTooLarge | ParseError::TooManyFields => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+                ParseError::UnsupportedVersion(_) => StatusCode::HTTP_VERSION_NOT_SUPPORTED,
+                _ => StatusCode::BAD_REQUEST,
+            }
+        }
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                ParseError::EmptyRequest => write!(f, "Empty request"),
+                ParseError::InvalidRequestLine => write!(f, "Invalid request line"),
+                ParseError::InvalidMethod => write!(f, "Invalid method"),
+                ParseError::UnknownMethod(method) => write!(f, "Unknown method: {}", method),
+                ParseError::InvalidTarget => write!(f, "Invalid request target"),
+                ParseError::InvalidVersion => write!(f, "Invalid http version"),
+                ParseError::UnsupportedVersion(version) => write!(f, "Unsupported http version: {}", version),
+                ParseError::RequestLineTooLong => write!(f, "Request line too long"),
+                ParseError::InvalidField(reason) => write!(f, "Invalid header field: {}", reason),
+                ParseError::InvalidLineEnding => write!(f, "Line not terminated by CRLF"),
+                ParseError::ObsoleteLineFolding => write!(f, "Obsolete line folding in header field"),
+                ParseError::HeadersTooLarge => write!(f, "Header fields too large"),
+                ParseError::TooManyFields => write!(f, "Too many header fields"),
+                ParseError::MissingHost => write!(f, "Missing or repeated host header"),
+            }
+        }
+    }
+
+    /// Parses the head of a request as defined by RFC 9112, the request line followed by
+    /// the header fields, each terminated by CRLF. The empty line ending the head is not
+    /// part of the input.
+    pub fn parse<'a>(request: &'a [u8], limits: &RequestLimits) -> Result<HttpRequest<'a>, ParseError> {
+        let lines = split_lines(request)?;
+        let (general_line, header_lines) = match lines.split_first() {
+            Some((line, header_lines)) if !line.is_empty() => (*line, header_lines),
+            _ => return Err(ParseError::EmptyRequest),
+        };
+        let general = parse_general(general_line, limits)?;
+        let headers = parse_headers(header_lines, limits)?;
+        // HTTP/1.1 requests have to name exactly one host, section 3.2.
+        if general.version == HttpVersion::One && headers.get("host").is_none_or(|host| host.contains(',')) {
+            return Err(ParseError::MissingHost);
+        }
+        Ok(HttpRequest {
+            general,
+            headers,
+            body: vec![],
+        })
+    }
+
+    /// Header names are stored in lowercase. Repeated fields are combined into one comma
+    /// separated value, which is equivalent for list based fields, section 5.3 of RFC 9110.
+    fn parse_headers(lines: &[&[u8]], limits: &RequestLimits) -> Result<HashMap<String, String>, ParseError> {
+        if lines.len() > limits.max_headers {
+            return Err(ParseError::TooManyFields);
+        }
+        if lines.iter().map(|line| line.len() + 2).sum::<usize>() > limits.max_header_size {
+            return Err(ParseError::HeadersTooLarge);
+        }
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for line in lines {
+            if line.starts_with(b" ") || line.starts_with(b"\t") {
+                return Err(ParseError::ObsoleteLineFolding);
+            }
+            let (name, value) = parse_field(line)?;
+            match headers.get_mut(&name) {
+                Some(existing) => match name.as_str() {
+                    // Differing lengths make the message framing ambiguous.
+                    "content-length" if *existing != value => {
+                        return Err(ParseError::InvalidField(String::from("conflicting content-length")))
+                    }
+                    "content-length" => (),
+                    "cookie" => {
+                        existing.push_str("; ");
+                        existing.push_str(&value);
+                    }
+                    _ => {
+                        existing.push_str(", ");
+                        existing.push_str(&value);
+                    }
+                },
+                None => {
+                    headers.insert(name, value);
                 }
             }
         }
+        Ok(headers)
     }
 
-    fn parse_headers(headers: &[&str]) -> HashMap<String, String> {
-        headers
+    /// Splits the head into lines, the last line has no line ending of its own.
+    fn split_lines(head: &[u8]) -> Result<Vec<&[u8]>, ParseError> {
+        let mut lines: Vec<&[u8]> = head.split(|b| *b == b'\n').collect();
+        let last = lines.len() - 1;
+        for line in &mut lines[..last] {
+            *line = line.strip_suffix(b"\r").ok_or(ParseError::InvalidLineEnding)?;
+        }
+        Ok(lines)
+    }
+
+    /// Splits `name: value` at the first colon, values like dates contain colons themselves.
+    /// Values are decoded as UTF-8, other bytes are replaced.
+    fn parse_field(line: &[u8]) -> Result<(String, String), ParseError> {
+        let colon = line
             .iter()
-            .map(|h| {
-                h.split(":")
-                    .map(|it| it.trim_start().trim_end())
-                    .collect::<Vec<&str>>()
-            })
-            .filter(|h| h.len() == 2)
-            .map(|h| (String::from(h[0]).to_lowercase(), String::from(h[1])))
-            .collect()
-    }
-
-    fn parse_general(general: &str) -> Result<GeneralRequest, &str> {
-        fn match_method(method: &str) -> Result<HttpMethod, &str> {
-            return match method.to_uppercase().as_str() {
-                "GET" => Ok(HttpMethod::Get),
-                "HEAD" => Ok(HttpMethod::Head),
-                "OPTIONS" => Ok(HttpMethod::Options),
-                "POST" => Ok(HttpMethod::Post),
-                "PUT" => Ok(HttpMethod::Put),
-                "DELETE" => Ok(HttpMethod::Delete),
-                _ => Err("Unknown method type"),
+            .position(|b| *b == b':')
+            .ok_or_else(|| ParseError::InvalidField(String::from("missing colon")))?;
+        let (name, value) = (&line[..colon], &line[colon + 1..]);
+        // Whitespace between name and colon has to be rejected, section 5.1.
+        if name.is_empty() || !name.iter().all(|b| is_token_char(*b)) {
+            return Err(ParseError::InvalidField(format!("invalid name {:?}", String::from_utf8_lossy(name))));
+        }
+        let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+        if value.iter().any(|b| *b == b'\r' || *b == b'\n' || *b == 0) {
+            return Err(ParseError::InvalidField(format!("invalid value for {}", name)));
+        }
+        let value = String::from_utf8_lossy(value);
+        Ok((name, String::from(value.trim_matches([' ', '\t']))))
+    }
+
+    fn parse_general<'a>(general: &'a [u8], limits: &RequestLimits) -> Result<GeneralRequest<'a>, ParseError> {
+        if general.len() > limits.max_request_line {
+            return Err(ParseError::RequestLineTooLong);
+        }
+        let general = std::str::from_utf8(general).map_err(|_| ParseError::InvalidRequestLine)?;
+        // Exactly one space separates the parts, section 3.
+        let general_split: Vec<&str> = general.split(' ').collect();
+        let (method, target, version) = match general_split.as_slice() {
+            [method, target, version] => (*method, *target, *version),
+            _ => return Err(ParseError::InvalidRequestLine),
+        };
+        let method = match_method(method)?;
+        let path = parse_target(target, method)?;
+        Ok(GeneralRequest {
+            method,
+            path: clean_path(path),
+            args: get_parameters_from_path(path),
+            version: match_version(version)?,
+        })
+    }
+
+    /// Methods are case-sensitive.
+    fn match_method(method: &str) -> Result<HttpMethod, ParseError> {
+        return match method {
+            "GET" => Ok(HttpMethod::Get),
+            "HEAD" => Ok(HttpMethod::Head),
+            "OPTIONS" => Ok(HttpMethod::Options),
+            "POST" => Ok(HttpMethod::Post),
+            "PUT" => Ok(HttpMethod::Put),
+            "PATCH" => Ok(HttpMethod::Patch),
+            "DELETE" => Ok(HttpMethod::Delete),
+            _ if !method.is_empty() && method.bytes().all(is_token_char) => Err(ParseError::UnknownMethod(String::from(method))),
+            _ => Err(ParseError::InvalidMethod),
+        };
+    }
+
+    /// Accepts the origin form, the absolute form and `*` for OPTIONS, section 3.2.
+    /// The absolute form is reduced to its path, the authority form is only used by CONNECT.
+    fn parse_target(target: &str, method: HttpMethod) -> Result<&str, ParseError> {
+        if target.is_empty() || !target.bytes().all(|b| b.is_ascii_graphic()) {
+            return Err(ParseError::InvalidTarget);
+        }
+        if target == "*" {
+            return match method {
+                HttpMethod::Options => Ok(target),
+                _ => Err(ParseError::InvalidTarget),
             };
         }
+        if target.starts_with('/') {
+            return Ok(target);
+        }
+        let scheme_end = target.find("://").ok_or(ParseError::InvalidTarget)?;
+        if !["http", "https"].iter().any(|scheme| target[..scheme_end].eq_ignore_ascii_case(scheme)) {
+            return Err(ParseError::InvalidTarget);
+        }
+        let authority_and_path = &target[scheme_end + 3..];
+        match authority_and_path.find(['/', '?']) {
+            Some(i) if authority_and_path[i..].starts_with('/') => Ok(&authority_and_path[i..]),
+            Some(_) => Err(ParseError::InvalidTarget),
+            None => Ok("/"),
+        }
+    }
+
+    /// `HTTP/1.1` and `HTTP/1.0` are served, later 1.x minor versions are answered as 1.1.
+    fn match_version(version: &str) -> Result<HttpVersion, ParseError> {
+        let digits = version.strip_prefix("HTTP/").ok_or(ParseError::InvalidVersion)?;
+        let (major, minor) = match digits.as_bytes() {
+            [major, b'.', minor] if major.is_ascii_digit() && minor.is_ascii_digit() => (major - b'0', minor - b'0'),
+            // `HTTP/2` and `HTTP/3` have no minor version.
+            [major] if major.is_ascii_digit() && *major != b'1' => (major - b'0', 0),
+            _ => return Err(ParseError::InvalidVersion),
+        };
+        return match (major, minor) {
+            (1, 0) => Ok(HttpVersion::OneZero),
+            (1, _) => Ok(HttpVersion::One),
+            _ => Err(ParseError::UnsupportedVersion(String::from(version))),
+        };
+    }
+
+    /// `tchar` from RFC 9110 section 5.6.2.
+    fn is_token_char(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn parse_head(head: &str) -> Result<HttpRequest<'_>, ParseError> {
+            parse(head.as_bytes(), &RequestLimits::default())
+        }
 
-        fn match_version(version: &str) -> Result<HttpVersion, &str> {
-            return match version.to_lowercase().as_str() {
-                "http/1.1" => Ok(HttpVersion::One),
-                "http/2" => Ok(HttpVersion::Two),
-                "http/3" => Ok(HttpVersion::Three),
-                _ => Err("Unknown method type"),
+        fn parse_error(head: &str) -> ParseError {
+            parse_head(head).unwrap_err()
+        }
+
+        #[test]
+        fn parses_the_request_line_and_headers() {
+            let request = parse_head("GET /files/a.txt?x=1#top HTTP/1.1\r\nHost: localhost\r\nAccept:  text/html \t").unwrap();
+            assert_eq!(request.general.method, HttpMethod::Get);
+            assert_eq!(request.general.path, "/files/a.txt");
+            assert_eq!(request.general.version, HttpVersion::One);
+            assert_eq!(request.headers.get("host").unwrap(), "localhost");
+            assert_eq!(request.headers.get("accept").unwrap(), "text/html");
+        }
+
+        #[test]
+        fn splits_fields_at_the_first_colon() {
+            let request = parse_head("GET / HTTP/1.1\r\nHost: localhost:8080\r\nIf-Modified-Since: Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+            assert_eq!(request.headers.get("host").unwrap(), "localhost:8080");
+            assert_eq!(request.headers.get("if-modified-since").unwrap(), "Sun, 06 Nov 1994 08:49:37 GMT");
+        }
+
+        #[test]
+        fn combines_repeated_fields() {
+            let request = parse_head(
+                "GET / HTTP/1.1\r\nHost: localhost\r\nAccept: text/html\r\naccept: application/json\r\nCookie: a=1\r\nCookie: b=2\r\nContent-Length: 3\r\nContent-Length: 3",
+            )
+            .unwrap();
+            assert_eq!(request.headers.get("accept").unwrap(), "text/html, application/json");
+            assert_eq!(request.headers.get("cookie").unwrap(), "a=1; b=2");
+            assert_eq!(request.headers.get("content-length").unwrap(), "3");
+        }
+
+        #[test]
+        fn rejects_conflicting_content_lengths() {
+            let error = parse_error("POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\nContent-Length: 4");
+            assert_eq!(error, ParseError::InvalidField(String::from("conflicting content-length")));
+            assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+        }
+
+        #[test]
+        fn rejects_invalid_fields() {
+            for head in [
+                "GET / HTTP/1.1\r\nHost: localhost\r\nno colon",
+                "GET / HTTP/1.1\r\nHost: localhost\r\nName : value",
+                "GET / HTTP/1.1\r\nHost: localhost\r\n: value",
+                "GET / HTTP/1.1\r\nHost: localhost\r\nName: a\rb",
+            ] {
+                assert!(matches!(parse_error(head), ParseError::InvalidField(_)), "{:?}", head);
+            }
+        }
+
+        #[test]
+        fn rejects_obsolete_line_folding() {
+            assert_eq!(parse_error("GET / HTTP/1.1\r\nHost: localhost\r\nX-Long: a\r\n b"), ParseError::ObsoleteLineFolding);
+            assert_eq!(parse_error("GET / HTTP/1.1\r\nHost: localhost\r\nX-Long: a\r\n\tb"), ParseError::ObsoleteLineFolding);
+        }
+
+        #[test]
+        fn rejects_bare_line_feeds() {
+            assert_eq!(parse_error("GET / HTTP/1.1\nHost: localhost"), ParseError::InvalidLineEnding);
+            assert_eq!(parse_error("GET / HTTP/1.1\r\nHost: localhost\nAccept: */*"), ParseError::InvalidLineEnding);
+        }
+
+        #[test]
+        fn requires_exactly_one_host_for_http_1_1() {
+            assert_eq!(parse_error("GET / HTTP/1.1"), ParseError::MissingHost);
+            assert_eq!(parse_error("GET / HTTP/1.1\r\nHost: a\r\nHost: b"), ParseError::MissingHost);
+            assert!(parse_head("GET / HTTP/1.0").is_ok());
+        }
+
+        #[test]
+        fn rejects_malformed_request_lines() {
+            assert_eq!(parse_error(""), ParseError::EmptyRequest);
+            assert_eq!(parse_error("\r\nHost: localhost"), ParseError::EmptyRequest);
+            assert_eq!(parse_error("GET /"), ParseError::InvalidRequestLine);
+            assert_eq!(parse_error("GET  / HTTP/1.1"), ParseError::InvalidRequestLine);
+            assert_eq!(parse_error("GET / HTTP/1.1 extra"), ParseError::InvalidRequestLine);
+            assert_eq!(parse_error("G(T / HTTP/1.1"), ParseError::InvalidMethod);
+            assert_eq!(parse_error("get / HTTP/1.1"), ParseError::UnknownMethod(String::from("get")));
+            assert_eq!(parse_error("BREW / HTTP/1.1"), ParseError::UnknownMethod(String::from("BREW")));
+        }
+
+        #[test]
+        fn accepts_the_supported_target_forms() {
+            let path = |head: &str| String::from(parse_head(head).unwrap().general.path);
+            assert_eq!(path("GET http://localhost/a/b?c=d HTTP/1.0"), "/a/b");
+            assert_eq!(path("GET HTTPS://localhost:8080 HTTP/1.0"), "/");
+            assert_eq!(path("OPTIONS * HTTP/1.0"), "*");
+            for head in [
+                "GET * HTTP/1.0",
+                "GET a/b HTTP/1.0",
+                "GET ftp://localhost/a HTTP/1.0",
+                "GET http://localhost?a=b HTTP/1.0",
+                "GET /caf\u{e9} HTTP/1.0",
+            ] {
+                assert_eq!(parse_error(head), ParseError::InvalidTarget, "{:?}", head);
+            }
+        }
+
+        #[test]
+        fn matches_the_http_version() {
+            let version = |head: &str| parse_head(head).map(|request| request.general.version);
+            assert_eq!(version("GET / HTTP/1.0"), Ok(HttpVersion::OneZero));
+            assert_eq!(version("GET / HTTP/1.1\r\nHost: localhost"), Ok(HttpVersion::One));
+            assert_eq!(version("GET / HTTP/1.9\r\nHost: localhost"), Ok(HttpVersion::One));
+            assert_eq!(version("GET / HTTP/2"), Err(ParseError::UnsupportedVersion(String::from("HTTP/2"))));
+            assert_eq!(version("GET / HTTP/3"), Err(ParseError::UnsupportedVersion(String::from("HTTP/3"))));
+            assert_eq!(version("GET / HTTP/2.0"), Err(ParseError::UnsupportedVersion(String::from("HTTP/2.0"))));
+            for head in ["GET / HTTP/1", "GET / http/1.1", "GET / HTTP/1.10", "GET / HTTP/x.y"] {
+                assert_eq!(version(head), Err(ParseError::InvalidVersion), "{:?}", head);
+            }
+        }
+
+        #[test]
+        fn enforces_the_limits() {
+            let limits = RequestLimits {
+                max_request_line: 32,
+                max_header_size: 64,
+                max_headers: 3,
+                ..RequestLimits::default()
             };
+            let long_target = format!("GET /{} HTTP/1.1\r\nHost: localhost", "a".repeat(32));
+            assert_eq!(parse(long_target.as_bytes(), &limits).unwrap_err(), ParseError::RequestLineTooLong);
+            let large_field = format!("GET / HTTP/1.1\r\nHost: localhost\r\nX-Large: {}", "a".repeat(64));
+            assert_eq!(parse(large_field.as_bytes(), &limits).unwrap_err(), ParseError::HeadersTooLarge);
+            let many_fields = "GET / HTTP/1.1\r\nHost: localhost\r\nA: 1\r\nB: 2\r\nC: 3";
+            assert_eq!(parse(many_fields.as_bytes(), &limits).unwrap_err(), ParseError::TooManyFields);
         }
 
-        let general_split: Vec<&str> = general.split(" ").map(|word| word.trim()).collect();
-        return match general_split.as_slice() {
-            [method, path] => {
-                let params = get_parameters_from_path(path);
-                return match match_method(method) {
-                    Ok(m) => Ok(GeneralRequest {
-                        method: m,
-                        path: clean_path(path),
-                        args: params,
-                        version: HttpVersion::One,
-                    }),
-                    Err(e) => Err(e),
-                };
+        #[test]
+        fn checks_incomplete_heads() {
+            let limits = RequestLimits {
+                max_request_line: 16,
+                max_header_size: 16,
+                ..RequestLimits::default()
+            };
+            assert_eq!(limits.check_incomplete_head(b"GET / HTTP/1.1"), Ok(()));
+            assert_eq!(limits.check_incomplete_head(&[b'a'; 17]), Err(ParseError::RequestLineTooLong));
+            let head = format!("GET / HTTP/1.1\r\n{}", "a".repeat(21));
+            assert_eq!(limits.check_incomplete_head(head.as_bytes()), Err(ParseError::HeadersTooLarge));
+        }
+
+        #[test]
+        fn maps_errors_to_status_codes() {
+            let cases = [
+                (ParseError::EmptyRequest, StatusCode::BAD_REQUEST),
+                (ParseError::InvalidRequestLine, StatusCode::BAD_REQUEST),
+                (ParseError::InvalidMethod, StatusCode::BAD_REQUEST),
+                (ParseError::UnknownMethod(String::from("BREW")), StatusCode::NOT_IMPLEMENTED),
+                (ParseError::InvalidTarget, StatusCode::BAD_REQUEST),
+                (ParseError::InvalidVersion, StatusCode::BAD_REQUEST),
+                (ParseError::UnsupportedVersion(String::from("HTTP/2")), StatusCode::HTTP_VERSION_NOT_SUPPORTED),
+                (ParseError::RequestLineTooLong, StatusCode::URI_TOO_LONG),
+                (ParseError::InvalidField(String::from("missing colon")), StatusCode::BAD_REQUEST),
+                (ParseError::InvalidLineEnding, StatusCode::BAD_REQUEST),
+                (ParseError::ObsoleteLineFolding, StatusCode::BAD_REQUEST),
+                (ParseError::HeadersTooLarge, StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE),
+                (ParseError::TooManyFields, StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE),
+                (ParseError::MissingHost, StatusCode::BAD_REQUEST),
+            ];
+            for (error, status) in cases {
+                assert_eq!(error.status(), status, "{}", error);
             }
-            // TODO: Refactor to use error handling for request method (and version)
-            [method, path, version] => Ok(GeneralRequest {
-                method: match_method(method).expect("valid method"),
-                path: clean_path(path),
-                args: get_parameters_from_path(path),
-                version: match_version(version).expect("valid version"),
-            }),
-            [] => Err("No general request information"),
-            _ => Err("Invalid general request information"),
-        };
+        }
     }
 }
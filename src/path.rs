@@ -1,5 +1,7 @@
 pub mod path {
-    use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+    use crate::request_helper::request_helper::percent_decode;
+    use std::fs;
+    use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
 
     pub fn remap(path: &Path, base_path: &Path, mapping_base: &Path) -> PathBuf {
         if !path.starts_with(base_path) {
@@ -8,4 +10,64 @@ pub mod path {
         Path::new(&MAIN_SEPARATOR.to_string())
             .join(mapping_base.join(path.strip_prefix(base_path).unwrap()))
     }
+
+    #[derive(Debug, PartialEq)]
+    pub enum ResolveError {
+        /// The path points outside the asset root or is malformed.
+        Forbidden,
+        NotFound,
+    }
+
+    /// Segments of the percent-encoded request path after decoding it and applying `.` and
+    /// `..` like `resolve_asset` does. `None` if the path does not decode to UTF-8.
+    pub fn normalized_segments(request_path: &str) -> Option<Vec<String>> {
+        let decoded = String::from_utf8(percent_decode(request_path)?).ok()?;
+        let mut segments = vec![];
+        for segment in decoded.split('/') {
+            match segment {
+                "" | "." => (),
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(String::from(segment)),
+            }
+        }
+        Some(segments)
+    }
+
+    /// Resolves the percent-encoded request path relative to the asset root. `.` and `..`
+    /// segments are normalized, but never above the root, and the result is canonicalized,
+    /// so symlinks pointing outside the root are refused as well.
+    pub fn resolve_asset(asset_base: &Path, request_path: &str) -> Result<PathBuf, ResolveError> {
+        let decoded = percent_decode(request_path).ok_or(ResolveError::Forbidden)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| ResolveError::Forbidden)?;
+        if decoded.contains(['\0', '\\']) {
+            return Err(ResolveError::Forbidden);
+        }
+        let mut segments: Vec<&str> = vec![];
+        for segment in decoded.split('/') {
+            match segment {
+                "" | "." => (),
+                ".." => {
+                    segments.pop().ok_or(ResolveError::Forbidden)?;
+                }
+                _ => {
+                    // Rejects anything the platform would not treat as a plain file name,
+                    // e.g. drive prefixes on windows.
+                    let mut components = Path::new(segment).components();
+                    match (components.next(), components.next()) {
+                        (Some(Component::Normal(_)), None) => segments.push(segment),
+                        _ => return Err(ResolveError::Forbidden),
+                    }
+                }
+            }
+        }
+        let root = fs::canonicalize(asset_base).map_err(|_| ResolveError::NotFound)?;
+        let resolved = fs::canonicalize(segments.iter().fold(root.clone(), |path, segment| path.join(segment)))
+            .map_err(|_| ResolveError::NotFound)?;
+        match resolved.starts_with(&root) {
+            true => Ok(resolved),
+            false => Err(ResolveError::Forbidden),
+        }
+    }
 }
@@ -0,0 +1,169 @@
+pub mod problem {
+    use crate::negotiation::negotiation::negotiate_media_type;
+    use crate::resource::resource::ParameterError;
+    use crate::response::response::{Response, ResponseBody};
+    use crate::status::status::StatusCode;
+
+    pub const PROBLEM_JSON: &str = "application/problem+json";
+
+    /// Representations of a problem in the order the server prefers them.
+    const PROBLEM_TYPES: [&str; 4] = [PROBLEM_JSON, "application/json", "text/html", "text/plain"];
+
+    /// Problem details as defined by RFC 9457, sent as JSON to clients that accept it and
+    /// as html or plain text otherwise.
+    #[derive(Debug, Clone)]
+    pub struct Problem {
+        status: StatusCode,
+        problem_type: Option<String>,
+        title: String,
+        detail: Option<String>,
+        extensions: Vec<(String, String)>,
+    }
+
+    impl Problem {
+        /// Problem of the status with its reason phrase as title.
+        pub fn create(status: impl Into<StatusCode>) -> Problem {
+            let status = status.into();
+            Problem {
+                status,
+                problem_type: None,
+                title: String::from(status.reason_phrase()),
+                detail: None,
+                extensions: vec![],
+            }
+        }
+
+        /// Uri identifying the problem type, `about:blank` if not set.
+        pub fn problem_type(mut self, uri: &str) -> Problem {
+            self.problem_type = Some(String::from(uri));
+            self
+        }
+
+        pub fn title(mut self, title: &str) -> Problem {
+            self.title = String::from(title);
+            self
+        }
+
+        pub fn detail(mut self, detail: &str) -> Problem {
+            self.detail = Some(String::from(detail));
+            self
+        }
+
+        /// Additional member of the JSON object, the value has to be valid JSON.
+        /// Extensions are not part of the html and text representations.
+        pub fn extension(mut self, name: &str, json_value: &str) -> Problem {
+            self.extensions.push((String::from(name), String::from(json_value)));
+            self
+        }
+
+        pub fn get_status(&self) -> StatusCode {
+            self.status
+        }
+
+        /// Response with the representation the `Accept` header asks for. If the client
+        /// accepts none of them it still gets JSON, error responses may ignore `Accept`.
+        pub fn to_response(&self, accept: Option<&str>) -> Response {
+            let (content_type, body) = self.render(accept);
+            Response::create(self.status)
+                .header("Content-Type", content_type)
+                .header("Vary", "Accept")
+                .body(ResponseBody::Text(body))
+        }
+
+        /// Content type and body of the negotiated representation.
+        pub fn render(&self, accept: Option<&str>) -> (&'static str, String) {
+            match negotiate_media_type(accept, &PROBLEM_TYPES) {
+                Some("text/html") => ("text/html; charset=utf-8", self.to_html()),
+                Some("text/plain") => ("text/plain; charset=utf-8", self.to_text()),
+                _ => (PROBLEM_JSON, self.to_json()),
+            }
+        }
+
+        pub fn to_json(&self) -> String {
+            let mut members = vec![
+                format!("\"type\":\"{}\"", escape_json(self.problem_type.as_deref().unwrap_or("about:blank"))),
+                format!("\"title\":\"{}\"", escape_json(&self.title)),
+                format!("\"status\":{}", self.status.code()),
+            ];
+            if let Some(detail) = &self.detail {
+                members.push(format!("\"detail\":\"{}\"", escape_json(detail)));
+            }
+            for (name, value) in &self.extensions {
+                members.push(format!("\"{}\":{}", escape_json(name), value));
+            }
+            format!("{{{}}}", members.join(","))
+        }
+
+        pub fn to_html(&self) -> String {
+            let heading = escape_html(&format!("{} {}", self.status.code(), self.title));
+            let detail = match &self.detail {
+                Some(detail) => format!("\n  <p>{}</p>", escape_html(detail)),
+                None => String::new(),
+            };
+            format!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n    <title>{}</title>\n</head>\n<body>\n  <h1>{}</h1>{}\n</body>\n</html>\n",
+                heading, heading, detail
+            )
+        }
+
+        pub fn to_text(&self) -> String {
+            match &self.detail {
+                Some(detail) => format!("{} {}\n{}\n", self.status.code(), self.title, detail),
+                None => format!("{} {}\n", self.status.code(), self.title),
+            }
+        }
+    }
+
+    /// 400 listing the invalid parameters, the JSON representation has them in its `errors` member.
+    pub fn invalid_parameters(errors: &[ParameterError]) -> Problem {
+        let errors_json: Vec<String> = errors
+            .iter()
+            .map(|e| {
+                format!(
+                    "{{\"parameter\":\"{}\",\"location\":\"{}\",\"message\":\"{}\"}}",
+                    escape_json(&e.name),
+                    e.location,
+                    escape_json(&e.message)
+                )
+            })
+            .collect();
+        let detail: Vec<String> = errors
+            .iter()
+            .map(|e| format!("{} ({}): {}", e.name, e.location, e.message))
+            .collect();
+        Problem::create(StatusCode::BAD_REQUEST)
+            .detail(&format!("Invalid parameters: {}", detail.join(", ")))
+            .extension("errors", &format!("[{}]", errors_json.join(",")))
+    }
+
+    pub fn escape_json(val: &str) -> String {
+        let mut escaped = String::with_capacity(val.len());
+        for c in val.chars() {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn escape_html(val: &str) -> String {
+        let mut escaped = String::with_capacity(val.len());
+        for c in val.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+}
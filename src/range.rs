@@ -0,0 +1,365 @@
+pub mod range {
+    use std::collections::VecDeque;
+    use std::fs::File;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use crate::response::response::{Response, ResponseBody};
+    use crate::status::status::StatusCode;
+
+    /// More ranges than this are answered with the full content, a client asking for that
+    /// many pieces is better served with one response.
+    const MAX_RANGES: usize = 64;
+
+    /// Inclusive byte range within the content.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct ByteRange {
+        pub start: u64,
+        pub end: u64,
+    }
+
+    impl ByteRange {
+        pub fn length(&self) -> u64 {
+            self.end - self.start + 1
+        }
+
+        pub fn content_range(&self, complete_length: u64) -> String {
+            format!("bytes {}-{}/{}", self.start, self.end, complete_length)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum RangeRequest {
+        /// No or an invalid range header, the full content is sent.
+        Full,
+        Partial(Vec<ByteRange>),
+        Unsatisfiable,
+    }
+
+    /// Parses a `Range` header for content of the given length. Unknown units and syntax
+    /// errors are ignored as required by RFC 9110, overlapping ranges are merged.
+    pub fn parse_range(header: &str, length: u64) -> RangeRequest {
+        let specs = match header.trim().split_once('=') {
+            Some((unit, specs)) if unit.trim().eq_ignore_ascii_case("bytes") => specs,
+            _ => return RangeRequest::Full,
+        };
+        let specs: Vec<&str> = specs.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+        if specs.is_empty() {
+            return RangeRequest::Full;
+        }
+        let mut ranges = vec![];
+        for spec in specs {
+            let (first, last) = match spec.split_once('-') {
+                Some(bounds) => bounds,
+                None => return RangeRequest::Full,
+            };
+            let range = match first {
+                // bytes=-500 are the last 500 bytes.
+                "" => match last.parse::<u64>() {
+                    Ok(suffix) if suffix > 0 && length > 0 => Some(ByteRange {
+                        start: length.saturating_sub(suffix),
+                        end: length - 1,
+                    }),
+                    Ok(_) => None,
+                    Err(_) => return RangeRequest::Full,
+                },
+                _ => {
+                    let start = match first.parse::<u64>() {
+                        Ok(start) => start,
+                        Err(_) => return RangeRequest::Full,
+                    };
+                    // bytes=500- is everything from byte 500 on.
+                    let end = match last {
+                        "" => u64::MAX,
+                        _ => match last.parse::<u64>() {
+                            Ok(end) if end >= start => end,
+                            _ => return RangeRequest::Full,
+                        },
+                    };
+                    match start < length {
+                        true => Some(ByteRange {
+                            start,
+                            end: end.min(length - 1),
+                        }),
+                        false => None,
+                    }
+                }
+            };
+            ranges.extend(range);
+        }
+        if ranges.is_empty() {
+            return RangeRequest::Unsatisfiable;
+        }
+        let ranges = merge(ranges);
+        match ranges.len() > MAX_RANGES {
+            true => RangeRequest::Full,
+            false => RangeRequest::Partial(ranges),
+        }
+    }
+
+    fn merge(mut ranges: Vec<ByteRange>) -> Vec<ByteRange> {
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<ByteRange> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end.saturating_add(1) => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
+    /// Turns the response for the whole file into a 206 answering the ranges, multiple
+    /// ranges are sent as `multipart/byteranges`. The ranges refer to the file on disk,
+    /// so partial content is never encoded.
+    pub fn partial_content(response: Response, mut file: File, complete_length: u64, ranges: &[ByteRange]) -> Result<Response, String> {
+        let response = response.status(StatusCode::PARTIAL_CONTENT);
+        match ranges {
+            [range] => {
+                file.seek(SeekFrom::Start(range.start))
+                    .map_err(|e| format!("Failed to seek in file: {}", e))?;
+                Ok(response
+                    .header("Content-Range", &range.content_range(complete_length))
+                    .body(ResponseBody::SizedStream(Box::new(file.take(range.length())), range.length())))
+            }
+            _ => {
+                let boundary = boundary();
+                let content_type = response.get_header("Content-Type").unwrap_or("application/octet-stream");
+                let body = MultipartRanges::create(file, ranges, complete_length, content_type, &boundary);
+                let length = body.content_length();
+                Ok(response
+                    .header("Content-Type", &format!("multipart/byteranges; boundary={}", boundary))
+                    .body(ResponseBody::SizedStream(Box::new(body), length)))
+            }
+        }
+    }
+
+    /// Separator for the parts of a multipart body, random enough to not show up in a file.
+    pub fn boundary() -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        format!("rust_http_{:x}", nanos)
+    }
+
+    /// Body of a `multipart/byteranges` response, the ranges are read from the file
+    /// one after another while the body is written.
+    pub struct MultipartRanges {
+        file: File,
+        pieces: VecDeque<Piece>,
+        length: u64,
+    }
+
+    enum Piece {
+        Bytes(Cursor<Vec<u8>>),
+        Section { start: u64, remaining: u64, positioned: bool },
+    }
+
+    impl MultipartRanges {
+        pub fn create(file: File, ranges: &[ByteRange], complete_length: u64, content_type: &str, boundary: &str) -> MultipartRanges {
+            let mut pieces = VecDeque::new();
+            for range in ranges {
+                let head = format!(
+                    "\r\n--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                    boundary,
+                    content_type,
+                    range.content_range(complete_length)
+                );
+                pieces.push_back(Piece::Bytes(Cursor::new(head.into_bytes())));
+                pieces.push_back(Piece::Section {
+                    start: range.start,
+                    remaining: range.length(),
+                    positioned: false,
+                });
+            }
+            pieces.push_back(Piece::Bytes(Cursor::new(format!("\r\n--{}--\r\n", boundary).into_bytes())));
+            let length = pieces
+                .iter()
+                .map(|piece| match piece {
+                    Piece::Bytes(bytes) => bytes.get_ref().len() as u64,
+                    Piece::Section { remaining, .. } => *remaining,
+                })
+                .sum();
+            MultipartRanges { file, pieces, length }
+        }
+
+        /// Length of the whole body, used as `Content-Length`.
+        pub fn content_length(&self) -> u64 {
+            self.length
+        }
+    }
+
+    impl Read for MultipartRanges {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            while let Some(piece) = self.pieces.front_mut() {
+                let read = match piece {
+                    Piece::Bytes(bytes) => bytes.read(buf)?,
+                    Piece::Section { start, remaining, positioned } => {
+                        if !*positioned {
+                            self.file.seek(SeekFrom::Start(*start))?;
+                            *positioned = true;
+                        }
+                        let max = (*remaining).min(buf.len() as u64) as usize;
+                        let read = self.file.read(&mut buf[..max])?;
+                        *remaining -= read as u64;
+                        if read == 0 && max > 0 {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::UnexpectedEof,
+                                "file is shorter than the requested range",
+                            ));
+                        }
+                        read
+                    }
+                };
+                if read > 0 || buf.is_empty() {
+                    return Ok(read);
+                }
+                self.pieces.pop_front();
+            }
+            Ok(0)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::fs;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        fn range(start: u64, end: u64) -> ByteRange {
+            ByteRange { start, end }
+        }
+
+        fn partial(ranges: &[ByteRange]) -> RangeRequest {
+            RangeRequest::Partial(ranges.to_vec())
+        }
+
+        /// File with the content `0123456789`, removed when the returned path is dropped.
+        struct TestFile(std::path::PathBuf);
+
+        impl TestFile {
+            fn create() -> TestFile {
+                let path = std::env::temp_dir().join(format!(
+                    "rust-http-range-{}-{}",
+                    std::process::id(),
+                    FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+                ));
+                fs::write(&path, "0123456789").unwrap();
+                TestFile(path)
+            }
+
+            fn open(&self) -> File {
+                File::open(&self.0).unwrap()
+            }
+        }
+
+        impl Drop for TestFile {
+            fn drop(&mut self) {
+                let _ = fs::remove_file(&self.0);
+            }
+        }
+
+        #[test]
+        fn parses_single_ranges() {
+            assert_eq!(parse_range("bytes=0-4", 10), partial(&[range(0, 4)]));
+            assert_eq!(parse_range(" Bytes = 2-2 ", 10), partial(&[range(2, 2)]));
+            assert_eq!(parse_range("bytes=5-", 10), partial(&[range(5, 9)]));
+            assert_eq!(parse_range("bytes=5-100", 10), partial(&[range(5, 9)]));
+        }
+
+        #[test]
+        fn parses_suffix_ranges() {
+            assert_eq!(parse_range("bytes=-3", 10), partial(&[range(7, 9)]));
+            assert_eq!(parse_range("bytes=-100", 10), partial(&[range(0, 9)]));
+        }
+
+        #[test]
+        fn merges_overlapping_and_adjacent_ranges() {
+            assert_eq!(parse_range("bytes=5-7,0-2,3-4", 10), partial(&[range(0, 7)]));
+            assert_eq!(parse_range("bytes=0-5,2-3,-2", 10), partial(&[range(0, 5), range(8, 9)]));
+            assert_eq!(parse_range("bytes=0-1,,4-", 10), partial(&[range(0, 1), range(4, 9)]));
+        }
+
+        #[test]
+        fn answers_ranges_outside_the_content_as_unsatisfiable() {
+            assert_eq!(parse_range("bytes=10-", 10), RangeRequest::Unsatisfiable);
+            assert_eq!(parse_range("bytes=20-30", 10), RangeRequest::Unsatisfiable);
+            assert_eq!(parse_range("bytes=-0", 10), RangeRequest::Unsatisfiable);
+            assert_eq!(parse_range("bytes=-5", 0), RangeRequest::Unsatisfiable);
+            assert_eq!(parse_range("bytes=0-", 0), RangeRequest::Unsatisfiable);
+            // Unsatisfiable ranges are dropped as long as another one can be served.
+            assert_eq!(parse_range("bytes=20-30,0-1", 10), partial(&[range(0, 1)]));
+        }
+
+        #[test]
+        fn ignores_invalid_headers() {
+            for header in ["items=0-1", "bytes", "bytes=", "bytes=1", "bytes=a-b", "bytes=5-1", "bytes=0-1,x", "bytes=--1"] {
+                assert_eq!(parse_range(header, 10), RangeRequest::Full, "{}", header);
+            }
+        }
+
+        #[test]
+        fn limits_the_number_of_ranges() {
+            let specs = |count: u64| (0..count).map(|i| format!("{}-{}", i * 2, i * 2)).collect::<Vec<String>>().join(",");
+            let length = 1000;
+            match parse_range(&format!("bytes={}", specs(MAX_RANGES as u64)), length) {
+                RangeRequest::Partial(ranges) => assert_eq!(ranges.len(), MAX_RANGES),
+                other => panic!("expected partial content, got {:?}", other),
+            }
+            assert_eq!(parse_range(&format!("bytes={}", specs(MAX_RANGES as u64 + 1)), length), RangeRequest::Full);
+            // Merged ranges count once.
+            let overlapping = vec!["0-5"; MAX_RANGES + 1].join(",");
+            assert_eq!(parse_range(&format!("bytes={}", overlapping), length), partial(&[range(0, 5)]));
+        }
+
+        #[test]
+        fn formats_content_ranges() {
+            assert_eq!(range(2, 5).length(), 4);
+            assert_eq!(range(2, 5).content_range(10), "bytes 2-5/10");
+        }
+
+        #[test]
+        fn writes_multipart_bodies_of_the_announced_length() {
+            let file = TestFile::create();
+            let ranges = [range(0, 1), range(4, 6), range(9, 9)];
+            let mut body = MultipartRanges::create(file.open(), &ranges, 10, "text/plain", "b");
+            let length = body.content_length();
+            let mut content = String::new();
+            body.read_to_string(&mut content).unwrap();
+            assert_eq!(content.len() as u64, length);
+            assert_eq!(
+                content,
+                "\r\n--b\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01\
+                 \r\n--b\r\nContent-Type: text/plain\r\nContent-Range: bytes 4-6/10\r\n\r\n456\
+                 \r\n--b\r\nContent-Type: text/plain\r\nContent-Range: bytes 9-9/10\r\n\r\n9\
+                 \r\n--b--\r\n"
+            );
+        }
+
+        #[test]
+        fn reads_multipart_bodies_in_small_pieces() {
+            let file = TestFile::create();
+            let ranges = [range(1, 3), range(7, 8)];
+            let mut expected = vec![];
+            MultipartRanges::create(file.open(), &ranges, 10, "text/plain", "b").read_to_end(&mut expected).unwrap();
+            let mut body = MultipartRanges::create(file.open(), &ranges, 10, "text/plain", "b");
+            let mut content = vec![];
+            let mut buf = [0u8; 3];
+            loop {
+                match body.read(&mut buf).unwrap() {
+                    0 => break,
+                    read => content.extend_from_slice(&buf[..read]),
+                }
+            }
+            assert_eq!(content, expected);
+            assert_eq!(content.len() as u64, body.content_length());
+        }
+
+        #[test]
+        fn fails_if_the_file_is_shorter_than_the_ranges() {
+            let file = TestFile::create();
+            let mut body = MultipartRanges::create(file.open(), &[range(0, 1), range(8, 12)], 13, "text/plain", "b");
+            let mut content = vec![];
+            assert_eq!(body.read_to_end(&mut content).unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
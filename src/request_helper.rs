@@ -1,52 +1,94 @@
 pub mod request_helper {
-    pub fn get_parameters_from_path(path: &str) -> Vec<RequestArgs> {
-        let query_params = get_query_params(path);
-        return query_params;
-    }
+    use std::borrow::Cow;
 
-    fn get_query_params(path: &str) -> Vec<RequestArgs> {
-        if !path.contains("?") {
-            return vec![];
-        }
-        let mut path_split = path.split("?");
-        let query_str = path_split.nth(1);
-        if let None = query_str {
-            return vec![];
-        }
-        match query_str {
+    /// Query parameters of a request target, in the order they appear.
+    pub fn get_parameters_from_path(path: &str) -> Vec<RequestArgs<'_>> {
+        match strip_fragment(path).split_once('?') {
+            Some((_, query)) => parse_query(query).into_iter().map(RequestArgs::Query).collect(),
             None => vec![],
-            Some(params) => {
-                return params
-                    .split("&")
-                    .collect::<Vec<&str>>()
-                    .iter()
-                    .map(|it| {
-                        let params_split: Vec<&str> = it.split("=").collect();
-                        let (name, value) =
-                            (params_split[0], params_split[1]);
-                        return RequestArgs::Query(RequestArgValue { name, value });
-                    })
-                    .collect()
-            }
         }
     }
 
+    /// Parses `application/x-www-form-urlencoded` pairs. Keys without `=` are kept with an
+    /// empty value and repeated keys are kept as separate entries.
+    pub fn parse_query(query: &str) -> Vec<RequestArgValue<'_>> {
+        query
+            .split('&')
+            .filter(|it| !it.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                RequestArgValue {
+                    name: decode_component(name, true),
+                    value: decode_component(value, true),
+                }
+            })
+            .collect()
+    }
+
+    /// Path of a request target without the query and fragment.
     pub fn clean_path(path: &str) -> &str {
-        match path.split("?").nth(0) {
+        match strip_fragment(path).split('?').next() {
             Some(val) => val,
             None => path,
         }
     }
 
-    #[derive(Debug, Copy, Clone)]
+    fn strip_fragment(path: &str) -> &str {
+        path.split('#').next().unwrap_or(path)
+    }
+
+    /// Decodes a path segment or query component. In queries `+` stands for a space.
+    /// Invalid escapes are kept as they are and invalid UTF-8 is replaced.
+    pub fn decode_component(value: &str, plus_as_space: bool) -> Cow<'_, str> {
+        let has_plus = plus_as_space && value.contains('+');
+        if !value.contains('%') && !has_plus {
+            return Cow::Borrowed(value);
+        }
+        let value: Cow<str> = match has_plus {
+            true => Cow::Owned(value.replace('+', " ")),
+            false => Cow::Borrowed(value),
+        };
+        match percent_decode(&value) {
+            Some(decoded) => Cow::Owned(String::from_utf8_lossy(&decoded).into_owned()),
+            None => value,
+        }
+    }
+
+    /// Decodes `%XX` escapes, returns `None` if an escape is incomplete or not hexadecimal.
+    pub fn percent_decode(value: &str) -> Option<Vec<u8>> {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' => {
+                    let hex = bytes.get(i + 1..i + 3)?;
+                    if !hex.iter().all(u8::is_ascii_hexdigit) {
+                        return None;
+                    }
+                    let hex = std::str::from_utf8(hex).ok()?;
+                    decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                    i += 3;
+                }
+                b => {
+                    decoded.push(b);
+                    i += 1;
+                }
+            }
+        }
+        Some(decoded)
+    }
+
+    #[derive(Debug, Clone)]
     pub enum RequestArgs<'a> {
         Query(RequestArgValue<'a>),
         Path(RequestArgValue<'a>),
     }
 
-    #[derive(Debug, Copy, Clone)]
+    /// Decoded name and value of a query or path parameter.
+    #[derive(Debug, Clone)]
     pub struct RequestArgValue<'a> {
-        pub name: &'a str,
-        pub value: &'a str
+        pub name: Cow<'a, str>,
+        pub value: Cow<'a, str>,
     }
 }
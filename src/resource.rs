@@ -1,38 +1,91 @@
 pub mod resource {
+    use std::borrow::Cow;
     use std::collections::HashMap;
-    use crate::request_helper::request_helper::{RequestArgValue};
+    use crate::request_helper::request_helper::{RequestArgValue, RequestArgs};
     use crate::request_helper::request_helper::RequestArgs::{Path, Query};
+    use crate::response::response::Response;
     use crate::types::types::HttpRequest;
+    use crate::validation::validation::validate;
+    use std::fmt;
+    use std::fmt::Formatter;
+
+    pub type ResourceFn = dyn Fn(&HashMap<&str, &RequestArgValue>, &HttpRequest) -> Response + Sync + Send;
 
     pub struct ResourceHandler {
         parameters: Vec<ResourceParameter>,
-        handler: Box<dyn Fn(&HashMap<&str, &RequestArgValue>) -> String + Sync + Send>,
+        handler: Box<ResourceFn>,
     }
 
     impl ResourceHandler {
-        pub fn new(handler: Box<dyn Fn(&HashMap<&str, &RequestArgValue>) -> String + Sync + Send>, parameters: Vec<ResourceParameter>) -> ResourceHandler {
+        pub fn new(handler: Box<ResourceFn>, parameters: Vec<ResourceParameter>) -> ResourceHandler {
             ResourceHandler {
                 parameters,
                 handler,
             }
         }
 
-        pub fn handle(&self, request: &HttpRequest) -> String {
-            let accepted_args = &request.general.args.iter().filter(|it| return match it {
+        /// Validates the parameters before calling the handler. Missing optional parameters
+        /// are filled with their defaults, every invalid parameter is reported.
+        pub fn handle(&self, request: &HttpRequest, path_args: &[RequestArgs]) -> Result<Response, Vec<ParameterError>> {
+            let accepted_args: HashMap<&str, &RequestArgValue> = request.general.args.iter().chain(path_args.iter()).filter(|it| return match it {
                 Query(arg) => {
                     let RequestArgValue { name, ..} = arg;
-                    self.parameters.iter().any(|p| &p.name == name && p.l_type == ResourceParameterLocation::Query)
+                    self.parameters.iter().any(|p| p.name == *name && p.l_type == ResourceParameterLocation::Query)
                 },
                 Path(arg) => {
                     let RequestArgValue { name, ..} = arg;
-                    self.parameters.iter().any(|p| &p.name == name && p.l_type == ResourceParameterLocation::Path)
+                    self.parameters.iter().any(|p| p.name == *name && p.l_type == ResourceParameterLocation::Path)
                 },
             }).map(|it| return match it {
-                Query(arg) => (arg.name, arg),
-                Path(arg) => (arg.name, arg),
+                Query(arg) => (arg.name.as_ref(), arg),
+                Path(arg) => (arg.name.as_ref(), arg),
             }).collect();
-            println!("Accepted args: {:?} vs all requested: {:?}", accepted_args, &request.general.args);
-            return (self.handler)(&accepted_args);
+            println!("Accepted args: {:?} vs all requested: {:?} {:?}", accepted_args, &request.general.args, path_args);
+
+            let mut errors = vec![];
+            let mut defaults = vec![];
+            for parameter in &self.parameters {
+                match accepted_args.get(parameter.name.as_str()) {
+                    Some(arg) => {
+                        if let Err(message) = validate(&arg.value, &parameter.p_type) {
+                            errors.push(ParameterError::new(parameter, message));
+                        }
+                    }
+                    None => match &parameter.default {
+                        Some(default) => defaults.push(RequestArgValue {
+                            name: Cow::Borrowed(&parameter.name),
+                            value: Cow::Borrowed(default),
+                        }),
+                        None if parameter.required => {
+                            errors.push(ParameterError::new(parameter, String::from("missing required parameter")))
+                        }
+                        None => (),
+                    },
+                }
+            }
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+            let mut args = accepted_args;
+            args.extend(defaults.iter().map(|it| (it.name.as_ref(), it)));
+            return Ok((self.handler)(&args, request));
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct ParameterError {
+        pub name: String,
+        pub location: ResourceParameterLocation,
+        pub message: String,
+    }
+
+    impl ParameterError {
+        fn new(parameter: &ResourceParameter, message: String) -> ParameterError {
+            ParameterError {
+                name: parameter.name.clone(),
+                location: parameter.l_type,
+                message,
+            }
         }
     }
 
@@ -40,35 +93,96 @@ pub mod resource {
         name: String,
         l_type: ResourceParameterLocation,
         p_type: ResourceParameterType,
+        required: bool,
+        default: Option<String>,
     }
 
-    #[derive(PartialEq)]
+    #[derive(Debug, Copy, Clone, PartialEq)]
     pub enum ResourceParameterLocation {
         Path,
         Query,
     }
 
-    // TODO: Better way to do this in rust?
+    impl fmt::Display for ResourceParameterLocation {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                ResourceParameterLocation::Path => write!(f, "path"),
+                ResourceParameterLocation::Query => write!(f, "query"),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
     pub enum ResourceParameterType {
         String,
         I8,
+        I16,
+        I32,
+        I64,
+        U8,
+        U16,
+        U32,
+        U64,
+        F32,
+        F64,
+        Bool,
+        Enum(Vec<String>),
+        Uuid,
+        /// Calendar date in the format `YYYY-MM-DD`.
+        Date,
+        /// RFC 3339 date time, e.g. `2022-05-01T12:30:00Z`.
+        DateTime,
     }
 
     impl ResourceParameter {
-        pub fn p_string(name: String, l_type: ResourceParameterLocation) -> ResourceParameter {
+        /// Creates a required parameter, see `optional` and `with_default`.
+        pub fn new(name: String, l_type: ResourceParameterLocation, p_type: ResourceParameterType) -> ResourceParameter {
             return ResourceParameter {
                 name,
                 l_type,
-                p_type: ResourceParameterType::String,
+                p_type,
+                required: true,
+                default: None,
             };
         }
 
+        pub fn p_string(name: String, l_type: ResourceParameterLocation) -> ResourceParameter {
+            return ResourceParameter::new(name, l_type, ResourceParameterType::String);
+        }
+
         pub fn p_i8(name: String, l_type: ResourceParameterLocation) -> ResourceParameter {
-            return ResourceParameter {
-                name,
-                l_type,
-                p_type: ResourceParameterType::I8,
-            };
+            return ResourceParameter::new(name, l_type, ResourceParameterType::I8);
+        }
+
+        pub fn optional(mut self) -> ResourceParameter {
+            self.required = false;
+            self
+        }
+
+        /// The default is passed to the handler if the parameter is missing in the request.
+        pub fn with_default(mut self, default: &str) -> ResourceParameter {
+            self.default = Some(String::from(default));
+            self
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn location(&self) -> &ResourceParameterLocation {
+            &self.l_type
+        }
+
+        pub fn p_type(&self) -> &ResourceParameterType {
+            &self.p_type
+        }
+
+        pub fn is_required(&self) -> bool {
+            self.required
+        }
+
+        pub fn default(&self) -> Option<&str> {
+            self.default.as_deref()
         }
     }
 }
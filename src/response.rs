@@ -1,84 +1,495 @@
 pub mod response {
-    use std::io::Write;
+    use std::fmt;
+    use std::fmt::Formatter;
+    use std::io::{BufWriter, ErrorKind, Read, Write};
     use std::net::TcpStream;
+    use std::path::PathBuf;
+    use std::sync::Arc;
     use flate2::Compression;
-    use flate2::write::{GzEncoder};
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use crate::cache::cache::encoded_entity_tag;
+    use crate::compression::compression::{CompressionLevel, CompressionPolicy};
+    use crate::encoding::encoding::ContentEncoding;
+    use crate::error_page::error_page::ErrorPages;
+    use crate::file::file::open_file;
+    use crate::problem::problem::Problem;
+    use crate::status::status::StatusCode;
+    use crate::types::types::HttpMethod;
+
+    /// Size of the pieces in which streamed bodies are written.
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    pub struct Response {
+        status: StatusCode,
+        headers: Vec<(String, String)>,
+        cookies: Vec<Cookie>,
+        body: ResponseBody,
+    }
+
+    pub enum ResponseBody {
+        Empty,
+        Bytes(Vec<u8>),
+        Text(String),
+        File(PathBuf),
+        Stream(Box<dyn Read + Send>),
+        /// Stream with the number of bytes it yields, sent with `Content-Length` unless encoded.
+        SizedStream(Box<dyn Read + Send>, u64),
+    }
+
+    impl Response {
+        pub fn create(status: impl Into<StatusCode>) -> Response {
+            Response {
+                status: status.into(),
+                headers: vec![],
+                cookies: vec![],
+                body: ResponseBody::Empty,
+            }
+        }
+
+        pub fn ok() -> Response {
+            Response::create(StatusCode::OK)
+        }
+
+        pub fn no_content() -> Response {
+            Response::create(StatusCode::NO_CONTENT)
+        }
+
+        pub fn not_found() -> Response {
+            Response::create(StatusCode::NOT_FOUND)
+        }
+
+        pub fn text(content: &str) -> Response {
+            Response::ok()
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(ResponseBody::Text(String::from(content)))
+        }
+
+        pub fn html(content: &str) -> Response {
+            Response::ok()
+                .header("Content-Type", "text/html; charset=utf-8")
+                .body(ResponseBody::Text(String::from(content)))
+        }
+
+        pub fn json(content: &str) -> Response {
+            Response::ok()
+                .header("Content-Type", "application/json")
+                .body(ResponseBody::Text(String::from(content)))
+        }
+
+        /// Temporary redirect, use `status` to change it to e.g. 301 or 303.
+        pub fn redirect(location: &str) -> Response {
+            Response::create(StatusCode::FOUND).header("Location", location)
+        }
+
+        pub fn status(mut self, status: impl Into<StatusCode>) -> Response {
+            self.status = status.into();
+            self
+        }
+
+        /// Sets the header, replacing an existing header with the same name.
+        pub fn header(mut self, name: &str, value: &str) -> Response {
+            self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+            self.headers.push((String::from(name), String::from(value)));
+            self
+        }
+
+        pub fn cookie(mut self, cookie: Cookie) -> Response {
+            self.cookies.push(cookie);
+            self
+        }
+
+        pub fn body(mut self, body: ResponseBody) -> Response {
+            self.body = body;
+            self
+        }
+
+        pub fn get_status(&self) -> StatusCode {
+            self.status
+        }
+
+        pub fn get_header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        }
+
+        pub fn get_headers(&self) -> &[(String, String)] {
+            &self.headers
+        }
+
+        pub fn get_cookies(&self) -> &[Cookie] {
+            &self.cookies
+        }
+
+        pub fn get_body(&self) -> &ResponseBody {
+            &self.body
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Cookie {
+        name: String,
+        value: String,
+        path: Option<String>,
+        domain: Option<String>,
+        max_age: Option<u64>,
+        secure: bool,
+        http_only: bool,
+        same_site: Option<String>,
+    }
+
+    impl Cookie {
+        pub fn new(name: &str, value: &str) -> Cookie {
+            Cookie {
+                name: String::from(name),
+                value: String::from(value),
+                path: None,
+                domain: None,
+                max_age: None,
+                secure: false,
+                http_only: false,
+                same_site: None,
+            }
+        }
+
+        pub fn path(mut self, path: &str) -> Cookie {
+            self.path = Some(String::from(path));
+            self
+        }
+
+        pub fn domain(mut self, domain: &str) -> Cookie {
+            self.domain = Some(String::from(domain));
+            self
+        }
+
+        pub fn max_age(mut self, seconds: u64) -> Cookie {
+            self.max_age = Some(seconds);
+            self
+        }
+
+        pub fn secure(mut self) -> Cookie {
+            self.secure = true;
+            self
+        }
+
+        pub fn http_only(mut self) -> Cookie {
+            self.http_only = true;
+            self
+        }
+
+        /// One of `Strict`, `Lax` or `None`.
+        pub fn same_site(mut self, same_site: &str) -> Cookie {
+            self.same_site = Some(String::from(same_site));
+            self
+        }
+    }
+
+    impl fmt::Display for Cookie {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "{}={}", self.name, self.value)?;
+            if let Some(path) = &self.path {
+                write!(f, "; Path={}", path)?;
+            }
+            if let Some(domain) = &self.domain {
+                write!(f, "; Domain={}", domain)?;
+            }
+            if let Some(max_age) = &self.max_age {
+                write!(f, "; Max-Age={}", max_age)?;
+            }
+            if self.secure {
+                write!(f, "; Secure")?;
+            }
+            if self.http_only {
+                write!(f, "; HttpOnly")?;
+            }
+            if let Some(same_site) = &self.same_site {
+                write!(f, "; SameSite={}", same_site)?;
+            }
+            Ok(())
+        }
+    }
 
     pub struct ResponseHandler {
         content_encoding: Option<String>,
-        writer: Box<dyn ResponseWriter>
+        writer: Box<dyn ResponseWriter>,
+        include_body: bool,
+        connection: Option<&'static str>,
+        legacy_client: bool,
+        vary: bool,
+        compression: Arc<CompressionPolicy>,
+        error_pages: Arc<ErrorPages>,
+        accept: Option<String>,
     }
 
     impl ResponseHandler {
 
         pub fn uncompressed() -> Box<ResponseHandler> {
-            ResponseHandler::create(Box::from(DefaultResponseWriter::uncompressed()), Option::None)
+            let writer = DefaultResponseWriter::create(ContentEncoding::Identity, CompressionLevel::default());
+            ResponseHandler::create(Box::from(writer), Option::None, Arc::new(CompressionPolicy::default()))
         }
 
-        pub fn gzip() -> Box<ResponseHandler> {
-            ResponseHandler::create(Box::from(DefaultResponseWriter::gzip()), Option::Some(String::from("gzip")))
+        /// Handler using the encoding negotiated with the client, its responses announce
+        /// that they vary with `Accept-Encoding`. The policy decides which bodies are encoded.
+        pub fn negotiated(encoding: ContentEncoding, compression: &Arc<CompressionPolicy>) -> Box<ResponseHandler> {
+            let content_encoding = match encoding {
+                ContentEncoding::Identity => None,
+                _ => Some(encoding.to_string()),
+            };
+            let writer = DefaultResponseWriter::create(encoding, compression.get_level());
+            let mut response_handler = ResponseHandler::create(Box::from(writer), content_encoding, Arc::clone(compression));
+            response_handler.vary = true;
+            response_handler
         }
 
-        fn create(writer: Box<dyn ResponseWriter>, content_encoding: Option<String>) -> Box<ResponseHandler> {
+        fn create(writer: Box<dyn ResponseWriter>, content_encoding: Option<String>, compression: Arc<CompressionPolicy>) -> Box<ResponseHandler> {
             Box::from(ResponseHandler {
                 content_encoding,
-                writer
+                writer,
+                include_body: true,
+                connection: None,
+                legacy_client: false,
+                vary: false,
+                compression,
+                error_pages: Arc::new(ErrorPages::default()),
+                accept: None,
             })
         }
 
-        pub fn ok(&self, out_stream: &TcpStream, content: &str) -> Result<(), String> {
-            let additional_headers = match &self.content_encoding {
-                Some(val) => format!("Content-Encoding: {}\r\n", val),
-                None => String::from("")
+        /// `Accept` header of the request, decides the format of generated error bodies.
+        pub fn set_accept(&mut self, accept: Option<&str>) {
+            self.accept = accept.map(String::from);
+        }
+
+        pub fn set_error_pages(&mut self, error_pages: Arc<ErrorPages>) {
+            self.error_pages = error_pages;
+        }
+
+        /// Announces whether the connection stays open after the response. HTTP/1.1
+        /// connections are persistent by default, HTTP/1.0 clients need an explicit header.
+        pub fn set_keep_alive(&mut self, keep_alive: bool, legacy_client: bool) {
+            self.legacy_client = legacy_client;
+            self.connection = match (keep_alive, legacy_client) {
+                (false, _) => Some("close"),
+                (true, true) => Some("keep-alive"),
+                (true, false) => None,
+            };
+        }
+
+        /// Responses to HEAD requests carry the headers of the GET response, but no body.
+        pub fn omit_body(&mut self) {
+            self.include_body = false;
+        }
+
+        pub fn service_unavailable(&self, out_stream: &TcpStream, retry_after_seconds: u32) -> Result<(), String> {
+            self.send(out_stream, Response::create(StatusCode::SERVICE_UNAVAILABLE).header("Retry-After", &retry_after_seconds.to_string()))
+        }
+
+        /// Sends a response without body, the body is generated from the status.
+        pub fn error(&self, out_stream: &TcpStream, status: StatusCode) -> Result<(), String> {
+            self.send(out_stream, Response::create(status))
+        }
+
+        /// Sends the response, error responses without a body get one from the error pages.
+        /// Bodies that already have a `Content-Encoding` and partial content are sent as they are.
+        pub fn send(&self, out_stream: &TcpStream, response: Response) -> Result<(), String> {
+            let accept = self.accept.as_deref();
+            // Files are opened before anything is written, a file that can not be opened is
+            // answered with an error instead of leaving the client without a response. If the
+            // error page can not be opened either, the error gets a generated body.
+            let response = match open_file_body(self.error_pages.apply(response, accept)) {
+                Ok(response) => response,
+                Err(status) => open_file_body(self.error_pages.apply(Response::create(status), accept))
+                    .unwrap_or_else(|status| Problem::create(status).to_response(accept)),
+            };
+            if !response.status.allows_body() {
+                return self.write(&self.head(&response), None, out_stream);
+            }
+            let encode = self.encodes(&response);
+            // The encoded bytes differ from the identity ones, so they get their own entity tag.
+            let response = match (&self.content_encoding, response.get_header("ETag")) {
+                (Some(encoding), Some(etag)) if encode => {
+                    let etag = encoded_entity_tag(etag, encoding);
+                    response.header("ETag", &etag)
+                }
+                _ => response,
             };
-            let headers = format!(
-                "HTTP/1.1 200 OK\r\n{}",
-                additional_headers
-            );
-            return self.write(headers.as_str(), Option::Some(String::from(content)), out_stream);
+            let headers = self.head(&response);
+            match response.body {
+                ResponseBody::Empty => self.write_content(headers, vec![], false, out_stream),
+                ResponseBody::Bytes(bytes) => self.write_content(headers, bytes, encode, out_stream),
+                ResponseBody::Text(text) => self.write_content(headers, text.into_bytes(), encode, out_stream),
+                ResponseBody::File(_) => unreachable!("files are opened before the head is built"),
+                ResponseBody::Stream(stream) => self.write_stream(headers, stream, None, encode, out_stream),
+                ResponseBody::SizedStream(stream, length) => match encode {
+                    true => self.write_stream(headers, stream, Some(length), true, out_stream),
+                    false => self.write_identity(headers, stream, length, out_stream),
+                },
+            }
+        }
+
+        /// Whether the body of the response is sent with the negotiated encoding. HTTP/1.0
+        /// clients get streams of known length unencoded, see `write_stream`.
+        fn encodes(&self, response: &Response) -> bool {
+            if response.get_header("Content-Encoding").is_some() || response.status == StatusCode::PARTIAL_CONTENT {
+                return false;
+            }
+            let content_type = response.get_header("Content-Type");
+            match &response.body {
+                ResponseBody::Empty | ResponseBody::File(_) => false,
+                ResponseBody::Bytes(bytes) => self.may_encode(content_type, Some(bytes.len() as u64)),
+                ResponseBody::Text(text) => self.may_encode(content_type, Some(text.len() as u64)),
+                ResponseBody::Stream(_) => self.may_encode(content_type, None),
+                ResponseBody::SizedStream(_, length) => !self.legacy_client && self.may_encode(content_type, Some(*length)),
+            }
         }
 
-        pub fn not_found(&self, out_stream: &TcpStream) -> Result<(), String> {
-            let res = format!("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
-            return self.write(res.as_str(), None, out_stream);
+        /// Whether the body is encoded with the negotiated encoding, see `CompressionPolicy`.
+        fn may_encode(&self, content_type: Option<&str>, length: Option<u64>) -> bool {
+            self.content_encoding.is_some() && length != Some(0) && self.compression.compresses(content_type, length)
         }
 
-        pub fn bad_request(&self, out_stream: &TcpStream) -> Result<(), String> {
-            let res = format!("HTTP/1.1 404 Bad Request\r\nContent-Length: 0\r\n\r\n");
-            return self.write(res.as_str(), None, out_stream);
+        /// Status line, headers and cookies of the response.
+        fn head(&self, response: &Response) -> String {
+            let mut headers = format!("HTTP/1.1 {} {}\r\n", response.status.code(), response.status.reason_phrase());
+            let mut vary_pending = self.vary;
+            for (name, value) in &response.headers {
+                if self.vary && name.eq_ignore_ascii_case("Vary") {
+                    vary_pending = false;
+                    if value.trim() != "*" && !value.to_ascii_lowercase().contains("accept-encoding") {
+                        headers.push_str(&format!("{}: {}, Accept-Encoding\r\n", name, value));
+                        continue;
+                    }
+                }
+                headers.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            if vary_pending {
+                headers.push_str("Vary: Accept-Encoding\r\n");
+            }
+            for cookie in &response.cookies {
+                headers.push_str(&format!("Set-Cookie: {}\r\n", cookie));
+            }
+            headers
+        }
+
+        fn write_content(&self, mut headers: String, content: Vec<u8>, encode: bool, out_stream: &TcpStream) -> Result<(), String> {
+            let encode = encode && !content.is_empty();
+            if let (Some(encoding), true) = (&self.content_encoding, encode) {
+                headers.push_str(&format!("Content-Encoding: {}\r\n", encoding));
+            }
+            let headers = self.with_connection(headers);
+            self.writer.write(&headers, Some(content), encode, self.include_body, out_stream)
         }
 
-        pub fn write(&self, headers: &str, content: Option<String>, out_stream: &TcpStream) -> Result<(), String> {
-            self.writer.write(headers, content, out_stream)
+        /// Encoded content and content of unknown length are sent with chunked transfer
+        /// encoding. HTTP/1.0 clients do not support it, they get files unencoded and
+        /// streams read into memory.
+        fn write_stream(&self, mut headers: String, mut content: Box<dyn Read + Send>, length: Option<u64>, encode: bool, out_stream: &TcpStream) -> Result<(), String> {
+            if self.legacy_client {
+                let length = match length {
+                    Some(length) => length,
+                    None => {
+                        let mut bytes = vec![];
+                        content
+                            .read_to_end(&mut bytes)
+                            .map_err(|e| format!("Failed to read response stream: {}", e))?;
+                        return self.write_content(headers, bytes, encode, out_stream);
+                    }
+                };
+                return self.write_identity(headers, content, length, out_stream);
+            }
+            if let (Some(encoding), true) = (&self.content_encoding, encode) {
+                headers.push_str(&format!("Content-Encoding: {}\r\n", encoding));
+            }
+            let headers = self.with_connection(headers);
+            self.writer.write_stream(&headers, &mut content, length, encode, self.include_body, out_stream)
+        }
+
+        fn write_identity(&self, headers: String, mut content: Box<dyn Read + Send>, length: u64, out_stream: &TcpStream) -> Result<(), String> {
+            let headers = self.with_connection(headers);
+            self.writer.write_stream(&headers, &mut content, Some(length), false, self.include_body, out_stream)
+        }
+
+        /// Writes the headers followed by the content. The content length header is only
+        /// added if there is content, use an empty vec for responses with an empty body.
+        pub fn write(&self, headers: &str, content: Option<Vec<u8>>, out_stream: &TcpStream) -> Result<(), String> {
+            let headers = self.with_connection(String::from(headers));
+            self.writer.write(&headers, content, false, self.include_body, out_stream)
+        }
+
+        fn with_connection(&self, headers: String) -> String {
+            match self.connection {
+                Some(connection) => format!("{}Connection: {}\r\n", headers, connection),
+                None => headers,
+            }
+        }
+    }
+
+    /// Replaces a file body with the opened file, the error status tells why it can not be opened.
+    fn open_file_body(mut response: Response) -> Result<Response, StatusCode> {
+        if let ResponseBody::File(path) = &response.body {
+            match open_file(&path.to_string_lossy()) {
+                Ok((file, metadata)) => response.body = ResponseBody::SizedStream(Box::new(file), metadata.len()),
+                Err(e) => {
+                    println!("{}", e);
+                    return Err(match path.exists() {
+                        true => StatusCode::INTERNAL_SERVER_ERROR,
+                        false => StatusCode::NOT_FOUND,
+                    });
+                }
+            }
         }
+        Ok(response)
+    }
+
+    pub fn join_methods(methods: &[HttpMethod]) -> String {
+        methods.iter().map(|m| m.to_string()).collect::<Vec<String>>().join(", ")
     }
 
     trait ResponseWriter {
-        fn write(&self, headers: &str, content: Option<String>, out_stream: &TcpStream) -> Result<(), String>;
+        fn write(&self, headers: &str, content: Option<Vec<u8>>, encode: bool, include_body: bool, out_stream: &TcpStream) -> Result<(), String>;
+
+        /// Copies the content to the stream in chunks. Without a length or with `encode` set the
+        /// body is sent with chunked transfer encoding, otherwise with a `Content-Length`.
+        fn write_stream(&self, headers: &str, content: &mut dyn Read, length: Option<u64>, encode: bool, include_body: bool, out_stream: &TcpStream) -> Result<(), String>;
     }
 
     struct DefaultResponseWriter {
-        header_encoder: Box<dyn ResponseEncoder>,
         content_encoder: Box<dyn ResponseEncoder>,
     }
 
     impl DefaultResponseWriter {
-        pub fn uncompressed() -> DefaultResponseWriter {
-            DefaultResponseWriter {
-                header_encoder: Box::from(PlainResponseEncoder {}),
-                content_encoder: Box::from(PlainResponseEncoder {})
-            }
-        }
-
-        pub fn gzip() -> DefaultResponseWriter {
-            DefaultResponseWriter {
-                header_encoder: Box::from(PlainResponseEncoder {}),
-                content_encoder: Box::from(GzipResponseEncoder {})
-            }
+        pub fn create(encoding: ContentEncoding, level: CompressionLevel) -> DefaultResponseWriter {
+            let flate_level = match level {
+                CompressionLevel::Fastest => Compression::fast(),
+                CompressionLevel::Default => Compression::default(),
+                CompressionLevel::Best => Compression::best(),
+            };
+            let content_encoder: Box<dyn ResponseEncoder> = match encoding {
+                ContentEncoding::Identity => Box::from(PlainResponseEncoder {}),
+                ContentEncoding::Brotli => Box::from(BrotliResponseEncoder {
+                    quality: match level {
+                        CompressionLevel::Fastest => 1,
+                        // The maximum of 11 is too slow for compressing on the fly.
+                        CompressionLevel::Default => 5,
+                        CompressionLevel::Best => 11,
+                    },
+                }),
+                ContentEncoding::Zstd => Box::from(ZstdResponseEncoder {
+                    level: match level {
+                        CompressionLevel::Fastest => 1,
+                        CompressionLevel::Default => 3,
+                        CompressionLevel::Best => 19,
+                    },
+                }),
+                ContentEncoding::Gzip => Box::from(GzipResponseEncoder { level: flate_level }),
+                ContentEncoding::Deflate => Box::from(DeflateResponseEncoder { level: flate_level }),
+            };
+            DefaultResponseWriter { content_encoder }
         }
 
         fn write_bytes(&self, bytes: &[u8], mut out_stream: &TcpStream) -> Result<(), String> {
-            match out_stream.write(bytes) {
+            match out_stream.write_all(bytes) {
                 Err(_) => Err(String::from("Failed to write response")),
                 Ok(_) => match out_stream.flush() {
                     Err(e) => {
@@ -88,17 +499,39 @@ pub mod response {
                 },
             }
         }
+
+        fn copy_body(&self, content: &mut dyn Read, length: Option<u64>, encode: bool, out: &mut dyn Write) -> std::io::Result<()> {
+            match length {
+                Some(length) if !encode => {
+                    let copied = std::io::copy(&mut content.take(length), out)?;
+                    if copied != length {
+                        return Err(std::io::Error::new(
+                            ErrorKind::UnexpectedEof,
+                            format!("content ended after {} of {} bytes", copied, length),
+                        ));
+                    }
+                }
+                _ => {
+                    let mut chunked = ChunkedWriter { out };
+                    let mut encoder = match encode {
+                        true => self.content_encoder.wrap(&mut chunked)?,
+                        false => PlainResponseEncoder {}.wrap(&mut chunked)?,
+                    };
+                    std::io::copy(content, &mut encoder)?;
+                    encoder.finish()?;
+                    chunked.finish()?;
+                }
+            }
+            out.flush()
+        }
     }
 
     impl ResponseWriter for DefaultResponseWriter {
-        fn write(&self, headers: &str, content: Option<String>, out_stream: &TcpStream) -> Result<(), String> {
-            let encoded_header_res = self.header_encoder.encode(headers);
-            if let Err(e) = encoded_header_res {
-                return Err(format!("Failed to write response: {}", e))
-            }
-            let encoded_header = encoded_header_res.unwrap();
-            let encoded_content_res = match content {
-                Some(c) => self.content_encoder.encode(&c),
+        fn write(&self, headers: &str, content: Option<Vec<u8>>, encode: bool, include_body: bool, out_stream: &TcpStream) -> Result<(), String> {
+            let encoded_header = headers.as_bytes().to_vec();
+            let encoded_content_res = match &content {
+                Some(c) if encode && !c.is_empty() => self.content_encoder.encode(c),
+                Some(c) => Ok(c.clone()),
                 None => {
                     Ok(vec![])
                 }
@@ -108,39 +541,162 @@ pub mod response {
             }
             let encoded_content = encoded_content_res.unwrap();
 
-            let content_length_header = format!("Content-Length: {}\r\n\r\n", encoded_content.len());
+            let content_length_header = match content {
+                Some(_) => format!("Content-Length: {}\r\n\r\n", encoded_content.len()),
+                None => String::from("\r\n"),
+            };
             let content_length_header_bytes = content_length_header.as_bytes().to_vec();
 
-            let res_bytes: Vec<u8> = [encoded_header, content_length_header_bytes, encoded_content].concat();
-            self.write_bytes(&*res_bytes, out_stream)
+            let res_bytes: Vec<u8> = match include_body {
+                true => [encoded_header, content_length_header_bytes, encoded_content].concat(),
+                false => [encoded_header, content_length_header_bytes].concat(),
+            };
+            self.write_bytes(&res_bytes, out_stream)
+        }
+
+        fn write_stream(&self, headers: &str, content: &mut dyn Read, length: Option<u64>, encode: bool, include_body: bool, out_stream: &TcpStream) -> Result<(), String> {
+            let framing_header = match (length, encode) {
+                (Some(length), false) => format!("Content-Length: {}\r\n\r\n", length),
+                _ => String::from("Transfer-Encoding: chunked\r\n\r\n"),
+            };
+            let mut out = BufWriter::with_capacity(CHUNK_SIZE, out_stream);
+            let res = out
+                .write_all(headers.as_bytes())
+                .and_then(|_| out.write_all(framing_header.as_bytes()))
+                .and_then(|_| match include_body {
+                    true => self.copy_body(content, length, encode, &mut out),
+                    false => out.flush(),
+                });
+            res.map_err(|e| format!("Failed to write response: {}", e))
+        }
+    }
+
+    /// Frames everything written to it as chunks of the chunked transfer encoding.
+    struct ChunkedWriter<'a> {
+        out: &'a mut dyn Write,
+    }
+
+    impl ChunkedWriter<'_> {
+        /// Writes the terminating zero length chunk.
+        fn finish(&mut self) -> std::io::Result<()> {
+            self.out.write_all(b"0\r\n\r\n")
+        }
+    }
+
+    impl Write for ChunkedWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            // A zero length chunk would end the body early.
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            self.out.write_all(format!("{:X}\r\n", buf.len()).as_bytes())?;
+            self.out.write_all(buf)?;
+            self.out.write_all(b"\r\n")?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.out.flush()
+        }
+    }
+
+    /// Writer applying a content encoding, `finish` writes the remaining encoded data.
+    trait EncodingWriter: Write {
+        fn finish(self: Box<Self>) -> std::io::Result<()>;
+    }
+
+    impl EncodingWriter for &mut dyn Write {
+        fn finish(self: Box<Self>) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<W: Write> EncodingWriter for GzEncoder<W> {
+        fn finish(self: Box<Self>) -> std::io::Result<()> {
+            GzEncoder::finish(*self).map(|_| ())
+        }
+    }
+
+    impl<W: Write> EncodingWriter for ZlibEncoder<W> {
+        fn finish(self: Box<Self>) -> std::io::Result<()> {
+            ZlibEncoder::finish(*self).map(|_| ())
+        }
+    }
+
+    impl<W: Write> EncodingWriter for brotli::CompressorWriter<W> {
+        fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+            // into_inner swallows errors, flushing first surfaces write failures.
+            self.flush()?;
+            (*self).into_inner();
+            Ok(())
+        }
+    }
+
+    impl<W: Write> EncodingWriter for zstd::stream::write::Encoder<'_, W> {
+        fn finish(self: Box<Self>) -> std::io::Result<()> {
+            zstd::stream::write::Encoder::finish(*self).map(|_| ())
         }
     }
 
     trait ResponseEncoder {
-        fn encode(&self, val: & str) -> Result<Vec<u8>, String>;
+        /// Wraps the output, so data written to the returned writer is encoded on the fly.
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>>;
+
+        fn encode(&self, val: &[u8]) -> Result<Vec<u8>, String> {
+            let mut encoded = vec![];
+            self.wrap(&mut encoded)
+                .and_then(|mut encoder| {
+                    encoder.write_all(val)?;
+                    encoder.finish()
+                })
+                .map_err(|e| format!("Failed to encode content: {}", e))?;
+            Ok(encoded)
+        }
     }
 
     struct PlainResponseEncoder {}
     impl ResponseEncoder for PlainResponseEncoder {
-        fn encode(&self, val: & str) -> Result<Vec<u8>, String> {
-            Ok(val.as_bytes().to_vec())
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>> {
+            Ok(Box::new(out))
         }
     }
 
-    struct GzipResponseEncoder {}
+    struct GzipResponseEncoder {
+        level: Compression,
+    }
     impl ResponseEncoder for GzipResponseEncoder {
-        fn encode(&self, val: & str) -> Result<Vec<u8>, String> {
-            let mut compressed = GzEncoder::new(Vec::new(), Compression::default());
-            if let Err(e) = compressed.write_all(val.as_bytes()) {
-                return Err(format!("Failed to write to compressor: {}", e));
-            }
-            let compressed_bytes_res = compressed.finish();
-            match compressed_bytes_res {
-                Ok(compressed_bytes) => {
-                    return Ok(compressed_bytes)
-                }
-                Err(e) => Err(format!("Failed to compress content: {}", e))
-            }
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>> {
+            Ok(Box::new(GzEncoder::new(out, self.level)))
+        }
+    }
+
+    /// `deflate` in http is the zlib format, not a raw deflate stream.
+    struct DeflateResponseEncoder {
+        level: Compression,
+    }
+    impl ResponseEncoder for DeflateResponseEncoder {
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>> {
+            Ok(Box::new(ZlibEncoder::new(out, self.level)))
+        }
+    }
+
+    const BROTLI_WINDOW_BITS: u32 = 22;
+
+    struct BrotliResponseEncoder {
+        quality: u32,
+    }
+    impl ResponseEncoder for BrotliResponseEncoder {
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>> {
+            Ok(Box::new(brotli::CompressorWriter::new(out, CHUNK_SIZE, self.quality, BROTLI_WINDOW_BITS)))
+        }
+    }
+
+    struct ZstdResponseEncoder {
+        level: i32,
+    }
+    impl ResponseEncoder for ZstdResponseEncoder {
+        fn wrap<'a>(&self, out: &'a mut dyn Write) -> std::io::Result<Box<dyn EncodingWriter + 'a>> {
+            Ok(Box::new(zstd::stream::write::Encoder::new(out, self.level)?))
         }
     }
 }
@@ -0,0 +1,85 @@
+pub mod route {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum RouteSegment {
+        Static(String),
+        Param(String),
+        Wildcard(String),
+    }
+
+    /// Route of an endpoint, e.g. `/users/{id}/posts/{post}` or `/files/*rest`.
+    #[derive(Debug, Clone)]
+    pub struct RouteTemplate {
+        segments: Vec<RouteSegment>,
+    }
+
+    impl RouteTemplate {
+        pub fn parse(path: &str) -> Result<RouteTemplate, String> {
+            let raw_segments: Vec<&str> = split_segments(path).into_iter().map(|(_, s)| s).collect();
+            let mut segments = vec![];
+            let mut names: Vec<String> = vec![];
+            for (i, raw) in raw_segments.iter().enumerate() {
+                let segment = if raw.starts_with('{') && raw.ends_with('}') {
+                    RouteSegment::Param(String::from(&raw[1..raw.len() - 1]))
+                } else if let Some(name) = raw.strip_prefix('*') {
+                    if i != raw_segments.len() - 1 {
+                        return Err(format!("Wildcard {} must be the last segment of {}", raw, path));
+                    }
+                    RouteSegment::Wildcard(String::from(name))
+                } else if raw.contains('{') || raw.contains('}') {
+                    return Err(format!("Invalid segment {} in route {}", raw, path));
+                } else {
+                    RouteSegment::Static(String::from(*raw))
+                };
+                if let RouteSegment::Param(name) | RouteSegment::Wildcard(name) = &segment {
+                    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        return Err(format!("Invalid parameter name in segment {} of route {}", raw, path));
+                    }
+                    if names.contains(name) {
+                        return Err(format!("Duplicate parameter {} in route {}", name, path));
+                    }
+                    names.push(name.clone());
+                }
+                segments.push(segment);
+            }
+            Ok(RouteTemplate { segments })
+        }
+
+        /// Route matching the path exactly, without interpreting parameters.
+        pub fn literal(path: &str) -> RouteTemplate {
+            let segments = split_segments(path)
+                .into_iter()
+                .map(|(_, s)| RouteSegment::Static(String::from(s)))
+                .collect();
+            RouteTemplate { segments }
+        }
+
+        /// Route matching the path and everything below it.
+        pub fn prefix(path: &str, wildcard: &str) -> RouteTemplate {
+            let mut route = RouteTemplate::literal(path);
+            route.segments.push(RouteSegment::Wildcard(String::from(wildcard)));
+            route
+        }
+
+        pub fn segments(&self) -> &[RouteSegment] {
+            &self.segments
+        }
+    }
+
+    /// Splits a path into its non empty segments together with their byte offset.
+    pub fn split_segments(path: &str) -> Vec<(usize, &str)> {
+        let mut segments = vec![];
+        let mut start = 0;
+        for (i, c) in path.char_indices() {
+            if c == '/' {
+                if i > start {
+                    segments.push((start, &path[start..i]));
+                }
+                start = i + 1;
+            }
+        }
+        if start < path.len() {
+            segments.push((start, &path[start..]));
+        }
+        segments
+    }
+}
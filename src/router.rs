@@ -0,0 +1,162 @@
+pub mod router {
+    use crate::request_helper::request_helper::{decode_component, RequestArgValue, RequestArgs};
+    use std::borrow::Cow;
+    use crate::route::route::{split_segments, RouteSegment, RouteTemplate};
+    use std::collections::HashMap;
+
+    /// Segment based routing tree. Lookups prefer static segments over parameters and
+    /// parameters over wildcards, so the most specific route wins independent of the
+    /// registration order.
+    #[derive(Debug, Clone, Default)]
+    pub struct Router {
+        root: RouteNode,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct RouteNode {
+        static_children: HashMap<String, RouteNode>,
+        param_child: Option<(String, Box<RouteNode>)>,
+        wildcard: Option<(String, Vec<usize>)>,
+        endpoints: Vec<usize>,
+    }
+
+    pub enum RouterError {
+        /// The route is already taken by the endpoint with the given index.
+        Conflict(usize),
+        /// A parameter with a different name is already registered at the same position.
+        ParameterMismatch(String),
+    }
+
+    impl Router {
+        pub fn create() -> Router {
+            Router::default()
+        }
+
+        /// Checks whether the route can be registered. `conflicts` decides whether an
+        /// endpoint already registered for the same route clashes with the new one.
+        pub fn check<F>(&self, route: &RouteTemplate, conflicts: F) -> Result<(), RouterError>
+        where
+            F: Fn(usize) -> bool,
+        {
+            let mut node = &self.root;
+            for segment in route.segments() {
+                let next = match segment {
+                    RouteSegment::Static(s) => node.static_children.get(s),
+                    RouteSegment::Param(name) => match &node.param_child {
+                        Some((existing, _)) if existing != name => {
+                            return Err(RouterError::ParameterMismatch(existing.clone()))
+                        }
+                        Some((_, child)) => Some(child.as_ref()),
+                        None => None,
+                    },
+                    RouteSegment::Wildcard(name) => {
+                        return match &node.wildcard {
+                            Some((existing, _)) if existing != name => {
+                                Err(RouterError::ParameterMismatch(existing.clone()))
+                            }
+                            Some((_, endpoints)) => Router::check_endpoints(endpoints, conflicts),
+                            None => Ok(()),
+                        };
+                    }
+                };
+                match next {
+                    Some(child) => node = child,
+                    None => return Ok(()),
+                }
+            }
+            Router::check_endpoints(&node.endpoints, conflicts)
+        }
+
+        fn check_endpoints<F>(endpoints: &[usize], conflicts: F) -> Result<(), RouterError>
+        where
+            F: Fn(usize) -> bool,
+        {
+            match endpoints.iter().find(|e| conflicts(**e)) {
+                Some(existing) => Err(RouterError::Conflict(*existing)),
+                None => Ok(()),
+            }
+        }
+
+        /// Registers the endpoint index for the route, call `check` beforehand.
+        pub fn insert(&mut self, route: &RouteTemplate, index: usize) {
+            let mut node = &mut self.root;
+            for segment in route.segments() {
+                match segment {
+                    RouteSegment::Static(s) => {
+                        node = node.static_children.entry(s.clone()).or_default();
+                    }
+                    RouteSegment::Param(name) => {
+                        let (_, child) = node
+                            .param_child
+                            .get_or_insert_with(|| (name.clone(), Box::default()));
+                        node = child;
+                    }
+                    RouteSegment::Wildcard(name) => {
+                        let (_, endpoints) = node
+                            .wildcard
+                            .get_or_insert_with(|| (name.clone(), vec![]));
+                        endpoints.push(index);
+                        return;
+                    }
+                }
+            }
+            node.endpoints.push(index);
+        }
+
+        /// Resolves the endpoint indices registered for the path together with the path arguments.
+        pub fn lookup<'a>(&'a self, path: &'a str) -> Option<(&'a [usize], Vec<RequestArgs<'a>>)> {
+            let segments = split_segments(path);
+            let mut args = vec![];
+            Router::lookup_node(&self.root, path, &segments, &mut args).map(|endpoints| (endpoints, args))
+        }
+
+        fn lookup_node<'a>(
+            node: &'a RouteNode,
+            path: &'a str,
+            segments: &[(usize, &'a str)],
+            args: &mut Vec<RequestArgs<'a>>,
+        ) -> Option<&'a [usize]> {
+            match segments.split_first() {
+                None => {
+                    if !node.endpoints.is_empty() {
+                        return Some(&node.endpoints);
+                    }
+                }
+                Some(((_, segment), rest)) => {
+                    // Routes are registered unencoded, so `/%61pi` has to reach the `/api` routes.
+                    let segment = decode_component(segment, false);
+                    if let Some(child) = node.static_children.get(segment.as_ref()) {
+                        if let Some(endpoints) = Router::lookup_node(child, path, rest, args) {
+                            return Some(endpoints);
+                        }
+                    }
+                    if let Some((name, child)) = &node.param_child {
+                        args.push(RequestArgs::Path(RequestArgValue {
+                            name: Cow::Borrowed(name),
+                            value: segment,
+                        }));
+                        if let Some(endpoints) = Router::lookup_node(child, path, rest, args) {
+                            return Some(endpoints);
+                        }
+                        args.pop();
+                    }
+                }
+            }
+            match &node.wildcard {
+                Some((name, endpoints)) if !endpoints.is_empty() => {
+                    let value = match segments.first() {
+                        Some((start, _)) => &path[*start..],
+                        None => "",
+                    };
+                    // The remainder stays encoded, decoding it could turn `%2F` into a separator.
+                    args.push(RequestArgs::Path(RequestArgValue {
+                        name: Cow::Borrowed(name),
+                        value: Cow::Borrowed(value),
+                    }));
+                    Some(endpoints)
+                }
+                _ => None,
+            }
+        }
+    }
+}
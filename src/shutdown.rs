@@ -0,0 +1,51 @@
+pub mod shutdown {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// How long `shutdown` tries to connect to the listener to wake the accept loop.
+    const WAKE_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Stops a running `WebServer`. Clones share the same state, so a handle can be moved
+    /// into a signal handler or another thread while the server keeps running.
+    #[derive(Debug, Clone, Default)]
+    pub struct ShutdownHandle {
+        requested: Arc<AtomicBool>,
+        wake_address: Option<SocketAddr>,
+    }
+
+    impl ShutdownHandle {
+        pub fn create() -> ShutdownHandle {
+            ShutdownHandle::default()
+        }
+
+        /// Handle for a server listening on the address, `shutdown` connects to it to wake
+        /// the accept loop waiting for the next connection.
+        pub fn listening_on(address: SocketAddr) -> ShutdownHandle {
+            let ip = match address.ip() {
+                IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                ip => ip,
+            };
+            ShutdownHandle {
+                requested: Arc::new(AtomicBool::new(false)),
+                wake_address: Some(SocketAddr::new(ip, address.port())),
+            }
+        }
+
+        /// Stops accepting new connections, `WebServer::run` returns once the open
+        /// connections are drained or the shutdown timeout has passed.
+        pub fn shutdown(&self) {
+            self.requested.store(true, Ordering::SeqCst);
+            if let Some(address) = self.wake_address {
+                // The connection only wakes the accept loop, it is closed right away.
+                let _ = TcpStream::connect_timeout(&address, WAKE_TIMEOUT);
+            }
+        }
+
+        pub fn is_shutdown(&self) -> bool {
+            self.requested.load(Ordering::SeqCst)
+        }
+    }
+}
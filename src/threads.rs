@@ -2,90 +2,114 @@ pub mod threads {
     use std::error::Error;
     use std::fmt;
     use std::fmt::{Display, Formatter};
-    use std::sync::atomic::{AtomicI8, Ordering};
-    use std::sync::mpsc::Sender;
-    use std::sync::{mpsc, Arc};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+    use std::sync::{mpsc, Arc, Mutex};
     use std::thread;
+    use std::thread::JoinHandle;
+    use std::time::{Duration, Instant};
 
+    type Job = Box<dyn FnOnce() + Send + 'static>;
+
+    const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Fixed pool of worker threads fed by a bounded job queue.
     #[derive(Debug)]
     pub struct ThreadHandler {
-        sender: Sender<ThreadMessageEvent>,
+        sender: Option<SyncSender<Job>>,
+        workers: Vec<JoinHandle<()>>,
         counter: Arc<ThreadCounter>,
     }
 
     #[derive(Debug)]
     pub struct ThreadCounter {
-        pub count: AtomicI8,
-        pub max_count: i8,
+        pub active: AtomicUsize,
+        pub queued: AtomicUsize,
     }
 
-    impl ThreadCounter {
-        pub fn can_create(&self) -> bool {
-            self.count.load(Ordering::SeqCst) < self.max_count
+    /// Marks a worker as busy until dropped, so the count stays correct on panics.
+    struct ActiveGuard<'a> {
+        counter: &'a ThreadCounter,
+    }
+
+    impl<'a> ActiveGuard<'a> {
+        fn new(counter: &'a ThreadCounter) -> ActiveGuard<'a> {
+            counter.queued.fetch_sub(1, Ordering::SeqCst);
+            counter.active.fetch_add(1, Ordering::SeqCst);
+            ActiveGuard { counter }
         }
     }
 
-    enum ThreadMessageEvent {
-        OPEN,
-        CLOSE,
-        ERROR(String),
+    impl Drop for ActiveGuard<'_> {
+        fn drop(&mut self) {
+            self.counter.active.fetch_sub(1, Ordering::SeqCst);
+        }
     }
 
     #[derive(Debug)]
     pub enum ThreadError {
-        NoRemainingThreads,
+        QueueFull,
+        Closed,
     }
 
     impl Display for ThreadError {
         fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
             match self {
-                ThreadError::NoRemainingThreads => write!(f, "No remaining threads."),
+                ThreadError::QueueFull => write!(f, "No remaining capacity in job queue."),
+                ThreadError::Closed => write!(f, "Thread pool is shut down."),
             }
         }
     }
 
     impl std::error::Error for ThreadError {}
 
-    impl fmt::Display for ThreadMessageEvent {
-        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-            match self {
-                ThreadMessageEvent::OPEN => write!(f, "Open thread"),
-                ThreadMessageEvent::CLOSE => write!(f, "Close thread"),
-                ThreadMessageEvent::ERROR(e) => write!(f, "Error when handling thread: {}", e),
-            }
-        }
-    }
-
     impl ThreadHandler {
-        pub fn create() -> ThreadHandler {
-            let (tx, rx) = mpsc::channel();
+        pub fn create(worker_count: usize, queue_size: usize) -> ThreadHandler {
+            let worker_count = worker_count.max(1);
+            let (tx, rx) = mpsc::sync_channel::<Job>(queue_size);
+            let receiver = Arc::new(Mutex::new(rx));
             let counter = Arc::from(ThreadCounter {
-                max_count: 4,
-                count: AtomicI8::from(0),
+                active: AtomicUsize::from(0),
+                queued: AtomicUsize::from(0),
             });
-            let thread_handler = ThreadHandler {
-                sender: tx,
-                counter: Arc::clone(&counter),
+            let workers = (0..worker_count)
+                .map(|i| {
+                    let receiver = Arc::clone(&receiver);
+                    let counter = Arc::clone(&counter);
+                    thread::Builder::new()
+                        .name(format!("worker-{}", i))
+                        .spawn(move || ThreadHandler::work(receiver, counter))
+                        .expect("unable to spawn worker thread")
+                })
+                .collect();
+            return ThreadHandler {
+                sender: Some(tx),
+                workers,
+                counter,
             };
-            let count_ref = counter.clone();
-            thread::spawn(move || loop {
-                let counter = Arc::clone(&count_ref);
-                if let Ok(message) = rx.recv() {
-                    match message {
-                        ThreadMessageEvent::OPEN => {
-                            counter.count.fetch_add(1, Ordering::SeqCst);
-                        }
-                        ThreadMessageEvent::CLOSE => {
-                            counter.count.fetch_sub(1, Ordering::SeqCst);
-                        }
-                        ThreadMessageEvent::ERROR(_) => (),
-                    }
-                    println!("{:?}", counter);
+        }
+
+        fn work(receiver: Arc<Mutex<Receiver<Job>>>, counter: Arc<ThreadCounter>) {
+            loop {
+                let job = {
+                    let receiver = receiver.lock().unwrap_or_else(|e| e.into_inner());
+                    receiver.recv()
+                };
+                let job = match job {
+                    Ok(job) => job,
+                    // The sender is gone, the pool is shutting down.
+                    Err(_) => return,
+                };
+                let _guard = ActiveGuard::new(&counter);
+                if catch_unwind(AssertUnwindSafe(job)).is_err() {
+                    println!("Job panicked, worker {:?} continues.", thread::current().name());
                 }
-            });
-            return thread_handler;
+                println!("{:?}", counter);
+            }
         }
 
+        /// Queues the job for the next free worker, fails if the queue is full.
         pub fn spawn<F, T, E>(&mut self, f: F) -> Result<(), ThreadError>
         where
             F: FnOnce() -> Result<T, E>,
@@ -94,28 +118,51 @@ pub mod threads {
             E: Error,
             E: Send + 'static,
         {
-            let thread_sender = self.sender.clone();
-            if !self.counter.can_create() {
-                return Err(ThreadError::NoRemainingThreads);
-            }
-            thread::spawn(move || {
-                thread_sender
-                    .send(ThreadMessageEvent::OPEN)
-                    .expect("unable to send message open");
-                match f() {
-                    Ok(_) => {
-                        thread_sender
-                            .send(ThreadMessageEvent::CLOSE)
-                            .expect("unable to send message close");
-                    }
-                    Err(e) => {
-                        thread_sender
-                            .send(ThreadMessageEvent::ERROR(e.to_string()))
-                            .expect("unable to send message error");
-                    }
-                };
+            let sender = match &self.sender {
+                Some(sender) => sender,
+                None => return Err(ThreadError::Closed),
+            };
+            let job: Job = Box::new(move || {
+                if let Err(e) = f() {
+                    println!("Error when handling job: {}", e);
+                }
             });
-            Ok(())
+            self.counter.queued.fetch_add(1, Ordering::SeqCst);
+            match sender.try_send(job) {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    self.counter.queued.fetch_sub(1, Ordering::SeqCst);
+                    match e {
+                        TrySendError::Full(_) => Err(ThreadError::QueueFull),
+                        TrySendError::Disconnected(_) => Err(ThreadError::Closed),
+                    }
+                }
+            }
+        }
+
+        /// Closes the queue and waits until the workers have finished the queued and running
+        /// jobs. Workers still busy after the timeout are detached, returns whether all finished.
+        pub fn shutdown(&mut self, timeout: Duration) -> bool {
+            self.sender = None;
+            let deadline = Instant::now() + timeout;
+            while self.workers.iter().any(|w| !w.is_finished()) && Instant::now() < deadline {
+                thread::sleep(JOIN_POLL_INTERVAL);
+            }
+            let (finished, unfinished): (Vec<JoinHandle<()>>, Vec<JoinHandle<()>>) =
+                self.workers.drain(..).partition(|w| w.is_finished());
+            for worker in finished {
+                let _ = worker.join();
+            }
+            unfinished.is_empty()
+        }
+    }
+
+    impl Drop for ThreadHandler {
+        fn drop(&mut self) {
+            self.sender = None;
+            for worker in self.workers.drain(..) {
+                let _ = worker.join();
+            }
         }
     }
 }
@@ -1,5 +1,7 @@
 pub mod types {
-    use crate::request_helper::request_helper::RequestArgs;
+    use crate::negotiation::negotiation::negotiate_media_type;
+    use crate::request_helper::request_helper::{decode_component, RequestArgValue, RequestArgs};
+    use std::borrow::Cow;
     use std::collections::HashMap;
     use std::fmt;
     use std::fmt::Formatter;
@@ -12,10 +14,52 @@ pub mod types {
         pub version: HttpVersion,
     }
 
-    #[derive(Debug)]
     pub struct HttpRequest<'a> {
         pub general: GeneralRequest<'a>,
         pub headers: HashMap<String, String>,
+        pub body: Vec<u8>,
+    }
+
+    impl HttpRequest<'_> {
+        pub fn body_str(&self) -> Result<&str, std::str::Utf8Error> {
+            std::str::from_utf8(&self.body)
+        }
+
+        /// Percent-decoded request path, `%2F` becomes a `/` just like any other escape.
+        pub fn decoded_path(&self) -> Cow<'_, str> {
+            decode_component(self.general.path, false)
+        }
+
+        /// Decoded query parameters in the order of the request target.
+        pub fn query_params(&self) -> impl Iterator<Item = &RequestArgValue<'_>> {
+            self.general.args.iter().filter_map(|arg| match arg {
+                RequestArgs::Query(value) => Some(value),
+                RequestArgs::Path(_) => None,
+            })
+        }
+
+        /// First value of the query parameter, empty for a parameter without value like `?flag`.
+        pub fn query(&self, name: &str) -> Option<&str> {
+            self.query_params().find(|arg| arg.name == name).map(|arg| arg.value.as_ref())
+        }
+
+        /// All values of a repeated query parameter like `?tag=a&tag=b`.
+        pub fn query_all(&self, name: &str) -> Vec<&str> {
+            self.query_params()
+                .filter(|arg| arg.name == name)
+                .map(|arg| arg.value.as_ref())
+                .collect()
+        }
+
+        pub fn has_query(&self, name: &str) -> bool {
+            self.query_params().any(|arg| arg.name == name)
+        }
+
+        /// The media type the client prefers among the available ones according to its
+        /// `Accept` header, see `negotiate_media_type`.
+        pub fn preferred_media_type<'b>(&self, available: &[&'b str]) -> Option<&'b str> {
+            negotiate_media_type(self.headers.get("accept").map(|it| it.as_str()), available)
+        }
     }
 
     #[derive(Debug, Copy, Clone, PartialEq)]
@@ -25,11 +69,27 @@ pub mod types {
         Get,
         Post,
         Put,
+        Patch,
         Delete,
     }
 
-    #[derive(Debug)]
+    impl HttpMethod {
+        pub fn all() -> Vec<HttpMethod> {
+            vec![
+                HttpMethod::Get,
+                HttpMethod::Head,
+                HttpMethod::Post,
+                HttpMethod::Put,
+                HttpMethod::Patch,
+                HttpMethod::Delete,
+                HttpMethod::Options,
+            ]
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
     pub enum HttpVersion {
+        OneZero,
         One,
         Two,
         Three,
@@ -43,6 +103,7 @@ pub mod types {
                 HttpMethod::Options => write!(f, "OPTIONS"),
                 HttpMethod::Post => write!(f, "POST"),
                 HttpMethod::Put => write!(f, "PUT"),
+                HttpMethod::Patch => write!(f, "PATCH"),
                 HttpMethod::Delete => write!(f, "DELETE"),
             }
         }
@@ -51,6 +112,7 @@ pub mod types {
     impl fmt::Display for HttpVersion {
         fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
             match self {
+                HttpVersion::OneZero => write!(f, "Http/1.0"),
                 HttpVersion::One => write!(f, "Http/1.1"),
                 HttpVersion::Two => write!(f, "Http/2"),
                 HttpVersion::Three => write!(f, "Http/3"),
@@ -58,6 +120,17 @@ pub mod types {
         }
     }
 
+    /// The body is only shown with its length, it may be large or hold credentials.
+    impl fmt::Debug for HttpRequest<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.debug_struct("HttpRequest")
+                .field("general", &self.general)
+                .field("headers", &self.headers)
+                .field("body", &format_args!("<{} bytes>", self.body.len()))
+                .finish()
+        }
+    }
+
     impl fmt::Display for HttpRequest<'_> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             write!(
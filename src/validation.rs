@@ -0,0 +1,139 @@
+pub mod validation {
+    use crate::resource::resource::ResourceParameterType;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn validate(value: &str, p_type: &ResourceParameterType) -> Result<(), String> {
+        match p_type {
+            ResourceParameterType::String => Ok(()),
+            ResourceParameterType::I8 => validate_number::<i8>(value, i8::MIN, i8::MAX),
+            ResourceParameterType::I16 => validate_number::<i16>(value, i16::MIN, i16::MAX),
+            ResourceParameterType::I32 => validate_number::<i32>(value, i32::MIN, i32::MAX),
+            ResourceParameterType::I64 => validate_number::<i64>(value, i64::MIN, i64::MAX),
+            ResourceParameterType::U8 => validate_number::<u8>(value, u8::MIN, u8::MAX),
+            ResourceParameterType::U16 => validate_number::<u16>(value, u16::MIN, u16::MAX),
+            ResourceParameterType::U32 => validate_number::<u32>(value, u32::MIN, u32::MAX),
+            ResourceParameterType::U64 => validate_number::<u64>(value, u64::MIN, u64::MAX),
+            ResourceParameterType::F32 => validate_float(value.parse::<f32>().map(f64::from)),
+            ResourceParameterType::F64 => validate_float(value.parse::<f64>()),
+            ResourceParameterType::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err(String::from("expected true or false")),
+            },
+            ResourceParameterType::Enum(variants) => match variants.iter().any(|v| v == value) {
+                true => Ok(()),
+                false => Err(format!("expected one of {}", variants.join(", "))),
+            },
+            ResourceParameterType::Uuid => match is_uuid(value) {
+                true => Ok(()),
+                false => Err(String::from("expected uuid")),
+            },
+            ResourceParameterType::Date => match is_date(value) {
+                true => Ok(()),
+                false => Err(String::from("expected date in format YYYY-MM-DD")),
+            },
+            ResourceParameterType::DateTime => match is_date_time(value) {
+                true => Ok(()),
+                false => Err(String::from("expected RFC 3339 date time")),
+            },
+        }
+    }
+
+    fn validate_number<T: FromStr + Display>(value: &str, min: T, max: T) -> Result<(), String> {
+        match value.parse::<T>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("expected integer between {} and {}", min, max)),
+        }
+    }
+
+    fn validate_float<E>(parsed: Result<f64, E>) -> Result<(), String> {
+        match parsed {
+            Ok(f) if f.is_finite() => Ok(()),
+            _ => Err(String::from("expected finite number")),
+        }
+    }
+
+    fn is_uuid(value: &str) -> bool {
+        let groups: Vec<&str> = value.split('-').collect();
+        let lengths: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+        lengths == [8, 4, 4, 4, 12] && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+    }
+
+    fn is_digits(value: &str, count: usize) -> bool {
+        value.len() == count && value.chars().all(|c| c.is_ascii_digit())
+    }
+
+    fn is_date(value: &str) -> bool {
+        let parts: Vec<&str> = value.split('-').collect();
+        if let [year, month, day] = parts.as_slice() {
+            if !is_digits(year, 4) || !is_digits(month, 2) || !is_digits(day, 2) {
+                return false;
+            }
+            let year: u32 = year.parse().unwrap();
+            let month: u32 = month.parse().unwrap();
+            let day: u32 = day.parse().unwrap();
+            let leap = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+            let days_in_month = match month {
+                1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+                4 | 6 | 9 | 11 => 30,
+                2 if leap => 29,
+                2 => 28,
+                _ => return false,
+            };
+            return day >= 1 && day <= days_in_month;
+        }
+        false
+    }
+
+    fn is_time(value: &str) -> bool {
+        let (time, fraction) = match value.split_once('.') {
+            Some((time, fraction)) => (time, Some(fraction)),
+            None => (value, None),
+        };
+        if let Some(fraction) = fraction {
+            if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+                return false;
+            }
+        }
+        let parts: Vec<&str> = time.split(':').collect();
+        match parts.as_slice() {
+            [hour, minute, second] if is_digits(hour, 2) && is_digits(minute, 2) && is_digits(second, 2) => {
+                hour.parse::<u32>().unwrap() < 24
+                    && minute.parse::<u32>().unwrap() < 60
+                    && second.parse::<u32>().unwrap() < 61
+            }
+            _ => false,
+        }
+    }
+
+    fn is_offset(value: &str) -> bool {
+        if value == "Z" || value == "z" {
+            return true;
+        }
+        match value.strip_prefix('+').or_else(|| value.strip_prefix('-')) {
+            Some(offset) => match offset.split_once(':') {
+                Some((hour, minute)) if is_digits(hour, 2) && is_digits(minute, 2) => {
+                    hour.parse::<u32>().unwrap() < 24 && minute.parse::<u32>().unwrap() < 60
+                }
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    fn is_date_time(value: &str) -> bool {
+        if value.len() < 11 || !value.is_char_boundary(10) {
+            return false;
+        }
+        let (date, rest) = value.split_at(10);
+        let time_with_offset = match rest.strip_prefix('T').or_else(|| rest.strip_prefix('t')) {
+            Some(t) => t,
+            None => return false,
+        };
+        let offset_start = time_with_offset
+            .find(['Z', 'z', '+', '-'])
+            .unwrap_or(time_with_offset.len());
+        let (time, offset) = time_with_offset.split_at(offset_start);
+        is_date(date) && is_time(time) && is_offset(offset)
+    }
+}
@@ -1,217 +1,637 @@
 pub mod web_server {
-    use std::collections::HashMap;
-    use crate::endpoint::endpoint::{EndpointHandler, EndpointProvider, EndpointType};
-    use crate::file::file::read_file;
-    use crate::parser::parser::parse;
-    use crate::path::path::remap;
-    use crate::resource::resource::{
-        ResourceHandler, ResourceParameter, ResourceParameterLocation,
-    };
+    use crate::body::body::{body_length, has_conflicting_framing};
+    use crate::cache::cache::{evaluate_preconditions, if_range_matches, CachePolicy, Precondition, Validators};
+    use crate::compression::compression::CompressionPolicy;
+    use crate::encoding::encoding::{accepted, negotiate, ContentEncoding};
+    use crate::endpoint::endpoint::{ASSET_WILDCARD, Endpoint, EndpointHandler, EndpointMatch, EndpointProvider, EndpointType};
+    use crate::file::file::open_file;
+    use crate::framing::framing::RequestFramer;
+    use crate::parser::parser::{parse, RequestLimits};
+    use crate::path::path::{resolve_asset, ResolveError};
+    use crate::middleware::middleware::Next;
+    use crate::problem::problem::invalid_parameters;
+    use crate::range::range::{parse_range, partial_content, RangeRequest};
+    use crate::request_helper::request_helper::RequestArgs;
+    use crate::shutdown::shutdown::ShutdownHandle;
+    use crate::status::status::StatusCode;
     use crate::threads::threads::ThreadHandler;
-    use crate::types::types::{HttpMethod, HttpRequest};
-    use std::io::Read;
-    use std::net::{TcpListener, TcpStream};
+    use crate::types::types::{HttpMethod, HttpRequest, HttpVersion};
+    use std::fs;
+    use std::io::{ErrorKind, Read};
+    use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
     use std::path::Path;
-    use crate::request_helper::request_helper::{RequestArgValue};
-    use crate::response::response::ResponseHandler;
-    use crate::template_engine::template_engine::TemplateEngine;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use crate::response::response::{join_methods, Response, ResponseBody, ResponseHandler};
 
     const MESSAGE_SIZE: usize = 1024;
+    const RETRY_AFTER_SECONDS: u32 = 1;
+    /// How long the unread request of a rejected connection is drained before closing it.
+    const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+    /// How often idle connections check for a shutdown request.
+    const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
     pub struct WebServer {
         tcp_listener: TcpListener,
         thread_handler: ThreadHandler,
         endpoint_handler: EndpointHandler,
-        template_engine: TemplateEngine
+        connection_config: ConnectionConfig,
+        shutdown: ShutdownHandle,
+        shutdown_timeout: Duration,
+        compression: Arc<CompressionPolicy>,
+        limits: RequestLimits,
+    }
+
+    /// Limits for persistent connections.
+    #[derive(Debug, Copy, Clone)]
+    pub struct ConnectionConfig {
+        /// How long to wait for the next request on an open connection.
+        pub idle_timeout: Duration,
+        /// How long to wait for the remaining data once a request has started.
+        pub read_timeout: Duration,
+        pub max_requests: usize,
+    }
+
+    impl Default for ConnectionConfig {
+        fn default() -> ConnectionConfig {
+            ConnectionConfig {
+                idle_timeout: Duration::from_secs(5),
+                read_timeout: Duration::from_secs(10),
+                max_requests: 100,
+            }
+        }
     }
 
     impl WebServer {
-        pub fn create() -> WebServer {
-            println!("Starting tcp bind to 8080.");
-            let tcp_listener =
-                TcpListener::bind("127.0.0.1:8080").expect("Unable to bind to port.");
-            println!("Tcp bind established, now listening.");
-            let thread_handler = ThreadHandler::create();
-            let endpoint_handler = EndpointHandler::create();
-            let template_engine = TemplateEngine {};
-            return WebServer {
-                tcp_listener,
-                thread_handler,
-                endpoint_handler,
-                template_engine
-            };
+        pub fn builder() -> WebServerBuilder {
+            WebServerBuilder::create()
+        }
+
+        pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+            self.tcp_listener.local_addr()
         }
 
+        pub fn port(&self) -> std::io::Result<u16> {
+            self.local_addr().map(|addr| addr.port())
+        }
+
+        pub fn shutdown_handle(&self) -> ShutdownHandle {
+            self.shutdown.clone()
+        }
+
+        /// Serves connections until a shutdown is requested through a `ShutdownHandle`,
+        /// then waits for the open connections to finish before returning.
         pub fn run(&mut self) -> std::io::Result<()> {
-            self.endpoint_handler
-                .register_static(String::from("files/dummy-website"), String::from("website"));
-            self.endpoint_handler
-                .register_assets(String::from("files/storage/"), String::from("storage"));
-            let template_engine = self.template_engine.clone();
-            self.endpoint_handler.register_resource(
-                String::from("math/sqr"),
-                String::from("sqr"),
-                Box::new(ResourceHandler::new(
-                    Box::from({move |params: &HashMap<&str, &RequestArgValue>| {
-                        let template = "<div><span>${n} * ${n}</span> = <span>${sqr}</span></div>\r\n";
-                        // TODO: Error handling
-                        let n = params.get("n").unwrap().value.parse::<i8>().unwrap();
-                        let res = (n * n).to_string();
-                        let context: HashMap<String, String> = HashMap::from([("sqr".to_string(), res), ("n".to_string(), n.to_string())]);
-                        template_engine.render(template, context)
-                    }}),
-                    vec![ResourceParameter::p_i8(
-                        String::from("n"),
-                        ResourceParameterLocation::Query,
-                    )],
-                )),
-            );
-
-            for stream in self.tcp_listener.incoming() {
-                match stream {
-                    Ok(_stream) => {
-                        println!(
-                            "Successfully created tcp connection with client {:?}",
-                            _stream.peer_addr()
-                        );
-                        let endpoint_provider = Box::new(self.endpoint_handler.to_provider());
-                        match self.thread_handler.spawn(|| {
-                            let web_server_thread_handler = WebServerThreadHandler {
-                                endpoint_handler: endpoint_provider,
-                            };
-                            web_server_thread_handler.handle_client(_stream)
-                        }) {
-                            Ok(()) => (),
-                            Err(e) => println!("{}", e),
-                        };
-                    }
+            let endpoint_provider = Arc::new(self.endpoint_handler.to_provider());
+            while !self.shutdown.is_shutdown() {
+                let stream = match self.tcp_listener.accept() {
+                    // `ShutdownHandle::shutdown` connects to wake the loop, that connection is not served.
+                    Ok(_) if self.shutdown.is_shutdown() => break,
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
                     Err(e) => {
                         println!("Failed to establish tcp connection with client: {:?}", e);
                         break;
                     }
-                }
+                };
+                println!("Successfully created tcp connection with client {:?}", stream.peer_addr());
+                let thread_endpoint_provider = Arc::clone(&endpoint_provider);
+                let connection_config = self.connection_config;
+                let shutdown = self.shutdown.clone();
+                let compression = Arc::clone(&self.compression);
+                let limits = self.limits;
+                let overflow_stream = stream.try_clone();
+                match self.thread_handler.spawn(move || {
+                    let web_server_thread_handler = WebServerThreadHandler {
+                        endpoint_handler: thread_endpoint_provider,
+                        connection_config,
+                        shutdown,
+                        compression,
+                        limits,
+                    };
+                    web_server_thread_handler.handle_client(stream)
+                }) {
+                    Ok(()) => (),
+                    Err(e) => {
+                        println!("{}", e);
+                        if let Ok(overflow_stream) = overflow_stream {
+                            let mut response_handler = ResponseHandler::uncompressed();
+                            response_handler.set_keep_alive(false, false);
+                            response_handler.set_error_pages(endpoint_provider.error_pages());
+                            response_handler
+                                .service_unavailable(&overflow_stream, RETRY_AFTER_SECONDS)
+                                .unwrap_or_else(|e| println!("{}", e));
+                            drain_and_close(overflow_stream);
+                        }
+                    }
+                };
+            }
+            println!("Stopped accepting connections, draining open connections.");
+            match self.thread_handler.shutdown(self.shutdown_timeout) {
+                true => println!("All connections closed, shutdown complete."),
+                false => println!("Shutdown timeout exceeded, abandoning remaining connections."),
             }
             Ok(())
         }
     }
 
+    pub struct WebServerBuilder {
+        address: String,
+        port: u16,
+        thread_limit: usize,
+        queue_size: usize,
+        endpoint_handler: EndpointHandler,
+        connection_config: ConnectionConfig,
+        shutdown_timeout: Duration,
+        compression: CompressionPolicy,
+        limits: RequestLimits,
+    }
+
+    impl WebServerBuilder {
+        pub fn create() -> WebServerBuilder {
+            WebServerBuilder {
+                address: String::from("127.0.0.1"),
+                port: 8080,
+                thread_limit: 4,
+                queue_size: 16,
+                endpoint_handler: EndpointHandler::create(),
+                connection_config: ConnectionConfig::default(),
+                shutdown_timeout: Duration::from_secs(30),
+                compression: CompressionPolicy::default(),
+                limits: RequestLimits::default(),
+            }
+        }
+
+        pub fn address(mut self, address: &str) -> WebServerBuilder {
+            self.address = String::from(address);
+            self
+        }
+
+        /// Port 0 lets the os pick a free port, use `WebServer::port` to read it back.
+        pub fn port(mut self, port: u16) -> WebServerBuilder {
+            self.port = port;
+            self
+        }
+
+        /// Number of worker threads handling connections.
+        pub fn thread_limit(mut self, thread_limit: usize) -> WebServerBuilder {
+            self.thread_limit = thread_limit;
+            self
+        }
+
+        /// Number of accepted connections waiting for a free worker, further connections
+        /// are answered with 503.
+        pub fn queue_size(mut self, queue_size: usize) -> WebServerBuilder {
+            self.queue_size = queue_size;
+            self
+        }
+
+        pub fn endpoint_handler(mut self, endpoint_handler: EndpointHandler) -> WebServerBuilder {
+            self.endpoint_handler = endpoint_handler;
+            self
+        }
+
+        pub fn idle_timeout(mut self, idle_timeout: Duration) -> WebServerBuilder {
+            self.connection_config.idle_timeout = idle_timeout;
+            self
+        }
+
+        pub fn read_timeout(mut self, read_timeout: Duration) -> WebServerBuilder {
+            self.connection_config.read_timeout = read_timeout;
+            self
+        }
+
+        /// The connection is closed after this many requests have been answered.
+        pub fn max_requests_per_connection(mut self, max_requests: usize) -> WebServerBuilder {
+            self.connection_config.max_requests = max_requests;
+            self
+        }
+
+        /// How long `run` waits for open connections to finish after a shutdown request.
+        pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> WebServerBuilder {
+            self.shutdown_timeout = shutdown_timeout;
+            self
+        }
+
+        /// Which responses are compressed for clients that accept an encoding.
+        pub fn compression(mut self, compression: CompressionPolicy) -> WebServerBuilder {
+            self.compression = compression;
+            self
+        }
+
+        /// Longer request lines are answered with 414, defaults to 8 KiB.
+        pub fn max_request_line(mut self, max_request_line: usize) -> WebServerBuilder {
+            self.limits.max_request_line = max_request_line;
+            self
+        }
+
+        /// Larger header sections are answered with 431, defaults to 16 KiB.
+        pub fn max_header_size(mut self, max_header_size: usize) -> WebServerBuilder {
+            self.limits.max_header_size = max_header_size;
+            self
+        }
+
+        /// Requests with more header fields are answered with 431, defaults to 100.
+        pub fn max_headers(mut self, max_headers: usize) -> WebServerBuilder {
+            self.limits.max_headers = max_headers;
+            self
+        }
+
+        /// Larger request bodies are answered with 413, defaults to 10 MiB.
+        pub fn max_body_size(mut self, max_body_size: usize) -> WebServerBuilder {
+            self.limits.max_body_size = max_body_size;
+            self
+        }
+
+        pub fn build(self) -> std::io::Result<WebServer> {
+            println!("Starting tcp bind to {}:{}.", self.address, self.port);
+            let tcp_listener = TcpListener::bind((self.address.as_str(), self.port))?;
+            let local_addr = tcp_listener.local_addr()?;
+            println!("Tcp bind established on {:?}, now listening.", local_addr);
+            Ok(WebServer {
+                tcp_listener,
+                thread_handler: ThreadHandler::create(self.thread_limit, self.queue_size),
+                endpoint_handler: self.endpoint_handler,
+                connection_config: self.connection_config,
+                shutdown: ShutdownHandle::listening_on(local_addr),
+                shutdown_timeout: self.shutdown_timeout,
+                compression: Arc::new(self.compression),
+                limits: self.limits,
+            })
+        }
+    }
+
     struct WebServerThreadHandler {
-        endpoint_handler: Box<EndpointProvider>,
+        endpoint_handler: Arc<EndpointProvider>,
+        connection_config: ConnectionConfig,
+        shutdown: ShutdownHandle,
+        compression: Arc<CompressionPolicy>,
+        limits: RequestLimits,
     }
 
     impl WebServerThreadHandler {
         fn handle_client(&self, mut stream: TcpStream) -> std::io::Result<()> {
-            let mut received: Vec<u8> = vec![];
-            let mut buf = [0u8; MESSAGE_SIZE];
-            let mut message;
+            let mut framer = RequestFramer::create(self.limits);
+            let mut handled_requests = 0;
             loop {
-                match stream.read(&mut buf) {
-                    Ok(bytes_read) => {
-                        if bytes_read == 0 {
-                            println!("Tcp stream exhausted.");
-                            break;
+                // Set once the first byte of the request arrived, it bounds reading head and body.
+                let mut request_deadline = None;
+                let head = loop {
+                    match framer.next_head() {
+                        Ok(Some(head)) => break head,
+                        Ok(None) => {
+                            if !self.read_more(&mut stream, &mut framer, handled_requests > 0, &mut request_deadline) {
+                                return Ok(());
+                            }
                         }
-                        received.extend_from_slice(&buf[..bytes_read]);
-                        message = std::str::from_utf8(&received).expect("invalid ut8");
-                        // println!("Read message: {}", message);
-                        let terminated = message.ends_with("\r\n\r\n");
-                        if terminated {
-                            println!(
-                                "Received terminated message, try processing as http request..."
-                            );
-                            self.process_http_request(message, &stream);
-                            received = vec![];
+                        Err(e) => {
+                            println!("{}", e);
+                            self.reject(&stream, e.status());
+                            return Ok(());
                         }
                     }
+                };
+                println!("Received terminated message, try processing as http request...");
+                let mut request = match parse(&head, &self.limits) {
+                    Ok(req) => req,
+                    Err(e) => {
+                        println!("{}", e);
+                        self.reject(&stream, e.status());
+                        return Ok(());
+                    }
+                };
+                let mut body_length = match body_length(&request.headers, self.limits.max_body_size) {
+                    Ok(l) => l,
                     Err(e) => {
-                        println!("Connection terminated: {:?}", e);
-                        break;
+                        println!("{}", e);
+                        self.reject(&stream, e.status());
+                        return Ok(());
+                    }
+                };
+                let body = loop {
+                    match framer.next_body(&mut body_length) {
+                        Ok(Some(body)) => break body,
+                        Ok(None) => {
+                            if !self.read_more(&mut stream, &mut framer, false, &mut request_deadline) {
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => {
+                            println!("{}", e);
+                            self.reject(&stream, e.status());
+                            return Ok(());
+                        }
                     }
                 };
+                request.body = body;
+                handled_requests += 1;
+                let keep_alive = handled_requests < self.connection_config.max_requests
+                    && !self.shutdown.is_shutdown()
+                    && !has_conflicting_framing(&request.headers)
+                    && WebServerThreadHandler::wants_keep_alive(&request);
+                // Pipelined requests wait in the framer, so responses go out in request order.
+                // A response that failed halfway leaves the connection in an unknown state.
+                let sent = self.process_http_request(&mut request, &stream, keep_alive);
+                if !keep_alive || !sent {
+                    println!("Closing connection after {} request(s).", handled_requests);
+                    return Ok(());
+                }
             }
-            Ok(())
         }
 
-        fn process_http_request(&self, message: &str, out_stream: &TcpStream) {
-            let request = parse(message);
-            match request {
-                Ok(req) => {
-                    println!("Received http request: {:?}", req);
-                    let compress = match req.headers.iter().find(|(name, _)| **name == String::from("accept-encoding")) {
-                        Some((_, val)) => val.split(",").map(|it| it.trim_start().trim_end()).collect::<Vec<&str>>().contains(&"gzip"),
-                        None => false
-                    };
-                    let response_handler = match compress {
-                        true => ResponseHandler::gzip(),
-                        false => ResponseHandler::uncompressed()
-                    };
-                    match (req.general.method, req.general.path) {
-                        (HttpMethod::Get, _) => {
-                            self.process_get_request(out_stream, response_handler, &req);
-                        }
-                        _ => response_handler.not_found(out_stream).map_or_else(|e| println!("{}", e), |val| val),
-                    }
+        /// HTTP/1.1 connections are persistent unless the client asks to close them,
+        /// HTTP/1.0 connections only if the client asks to keep them alive.
+        fn wants_keep_alive(request: &HttpRequest) -> bool {
+            let connection_options: Vec<String> = match request.headers.get("connection") {
+                Some(val) => val.split(',').map(|it| it.trim().to_lowercase()).collect(),
+                None => vec![],
+            };
+            match request.general.version {
+                HttpVersion::OneZero => connection_options.iter().any(|it| it == "keep-alive"),
+                _ => !connection_options.iter().any(|it| it == "close"),
+            }
+        }
+
+        /// Reads the next chunk of data. While waiting for a new request on a persistent
+        /// connection the wait is split into short slices, so the connection can be closed
+        /// as soon as the server shuts down. Once the request has started, all reads share the
+        /// deadline set when its first byte arrived, a client trickling data cannot extend it.
+        fn read_more(&self, stream: &mut TcpStream, framer: &mut RequestFramer, between_requests: bool, request_deadline: &mut Option<Instant>) -> bool {
+            let mut buf = [0u8; MESSAGE_SIZE];
+            let idle = framer.is_empty();
+            if !idle && request_deadline.is_none() {
+                *request_deadline = Some(Instant::now() + self.connection_config.read_timeout);
+            }
+            let deadline = request_deadline.unwrap_or_else(|| Instant::now() + self.connection_config.idle_timeout);
+            loop {
+                if between_requests && idle && self.shutdown.is_shutdown() {
+                    println!("Server is shutting down, closing idle connection.");
+                    return false;
+                }
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    println!("Connection timed out.");
+                    return false;
                 }
+                let wait = match idle {
+                    true => remaining.min(SHUTDOWN_POLL_INTERVAL),
+                    false => remaining,
+                };
+                if let Err(e) = stream.set_read_timeout(Some(wait)) {
+                    println!("Unable to set read timeout: {:?}", e);
+                    return false;
+                }
+                return match stream.read(&mut buf) {
+                    Ok(0) => {
+                        println!("Tcp stream exhausted.");
+                        false
+                    }
+                    Ok(bytes_read) => {
+                        framer.push(&buf[..bytes_read]);
+                        request_deadline.get_or_insert_with(|| Instant::now() + self.connection_config.read_timeout);
+                        true
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => continue,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        println!("Connection terminated: {:?}", e);
+                        false
+                    }
+                };
+            }
+        }
+
+        /// Answers a request that could not be read and closes the connection.
+        fn reject(&self, out_stream: &TcpStream, status: StatusCode) {
+            let mut response_handler = ResponseHandler::uncompressed();
+            response_handler.set_keep_alive(false, false);
+            response_handler.set_error_pages(self.endpoint_handler.error_pages());
+            response_handler.error(out_stream, status).unwrap_or_else(|e| println!("{}", e));
+            if let Ok(stream) = out_stream.try_clone() {
+                drain_and_close(stream);
+            }
+        }
+
+        /// Runs the request through the middleware registered for its path, the innermost
+        /// step dispatches it to the endpoint. The response is negotiated with the request
+        /// as the middleware left it. Returns whether the response was sent completely.
+        fn process_http_request(&self, req: &mut HttpRequest, out_stream: &TcpStream, keep_alive: bool) -> bool {
+            println!("Received http request: {}", req);
+            let middleware = self.endpoint_handler.middleware_for(route_path(req.general.path));
+            let dispatch = |request: &HttpRequest| self.dispatch(request);
+            let response = Next::create(&middleware, &dispatch).run(req);
+            let encoding = negotiate(req.headers.get("accept-encoding").map(|it| it.as_str()));
+            let mut response_handler = ResponseHandler::negotiated(encoding, &self.compression);
+            response_handler.set_error_pages(self.endpoint_handler.error_pages());
+            response_handler.set_accept(req.headers.get("accept").map(|it| it.as_str()));
+            if req.general.method == HttpMethod::Head {
+                response_handler.omit_body();
+            }
+            response_handler.set_keep_alive(keep_alive, req.general.version == HttpVersion::OneZero);
+            match response_handler.send(out_stream, response) {
+                Ok(()) => true,
                 Err(e) => {
                     println!("{}", e);
-                    let response_handler = ResponseHandler::uncompressed();
-                    response_handler.bad_request(out_stream).map_or_else(|e| println!("{}", e), |val| val)
+                    false
                 }
             }
         }
 
-        fn process_get_request(&self, out_stream: &TcpStream, response_handler: Box<ResponseHandler>, request: &HttpRequest) {
-            let path = &request.general.path;
-            let corrected_path = match path.len() > 1 && path.ends_with("/") {
-                true => &path[..path.len() - 1],
-                false => &path,
-            };
-            println!("Received GET request to path {}", corrected_path);
-            match self.get_file_content(corrected_path, request) {
-                Ok(content) => {
-                    response_handler.ok(out_stream, content.as_str()).map_or_else(|e| println!("{}", e), |val| val);
-                }
-                Err(_) => {
+        fn dispatch(&self, req: &HttpRequest) -> Response {
+            let corrected_path = route_path(req.general.path);
+            println!("Received {} request to path {}", req.general.method, corrected_path);
+            if req.general.method == HttpMethod::Options && corrected_path == "*" {
+                return Response::ok().header("Allow", &join_methods(&HttpMethod::all()));
+            }
+            match self.endpoint_handler.match_endpoint(corrected_path, req.general.method) {
+                EndpointMatch::Found(endpoint, path_args) => self.process_request(endpoint, &path_args, req),
+                EndpointMatch::MethodNotAllowed(allowed) => match req.general.method {
+                    HttpMethod::Options => Response::ok().header("Allow", &join_methods(&allowed)),
+                    _ => {
+                        println!("--> method not allowed");
+                        Response::create(StatusCode::METHOD_NOT_ALLOWED).header("Allow", &join_methods(&allowed))
+                    }
+                },
+                EndpointMatch::NotFound => {
                     println!("--> not found");
-                    response_handler.not_found(out_stream).map_or_else(|e| println!("{}", e), |val| val)
+                    Response::not_found()
                 }
-            };
+            }
         }
 
-        fn get_file_content(&self, path: &str, request: &HttpRequest) -> Result<String, String> {
-            let endpoint = self
-                .endpoint_handler
-                .match_endpoint(String::from(path), HttpMethod::Get);
-            match endpoint {
-                Some(e) => {
-                    let endpoint_type = &e.endpoint_type;
-                    match endpoint_type {
-                        EndpointType::StaticAsset(static_endpoint) => {
-                            let asset_path = &static_endpoint.asset_path;
-                            return read_file(asset_path);
-                        }
-                        EndpointType::Assets(asset_endpoint) => {
-                            let asset_path = remap(
-                                Path::new(path),
-                                Path::new(&e.path),
-                                Path::new(&asset_endpoint.asset_base),
-                            )
-                            .into_os_string()
-                            .into_string()
-                            .unwrap();
-                            return read_file(&asset_path);
+        fn process_request(&self, endpoint: &Endpoint, path_args: &[RequestArgs], request: &HttpRequest) -> Response {
+            let asset_path = match &endpoint.endpoint_type {
+                EndpointType::StaticAsset(static_endpoint) => static_endpoint.asset_path.clone(),
+                EndpointType::Assets(asset_endpoint) => {
+                    let request_path = path_args
+                        .iter()
+                        .find_map(|arg| match arg {
+                            RequestArgs::Path(value) if value.name == ASSET_WILDCARD => Some(value.value.as_ref()),
+                            _ => None,
+                        })
+                        .unwrap_or("");
+                    match resolve_asset(Path::new(&asset_endpoint.asset_base), request_path) {
+                        Ok(asset_path) => asset_path.to_string_lossy().into_owned(),
+                        Err(ResolveError::Forbidden) => {
+                            println!("--> forbidden");
+                            return Response::create(StatusCode::FORBIDDEN);
                         }
-                        EndpointType::Resource(resource_endpoint) => {
-                            return Ok(self.endpoint_handler.execute(resource_endpoint, request));
+                        Err(ResolveError::NotFound) => {
+                            println!("--> not found");
+                            return Response::not_found();
                         }
                     }
                 }
-                None => {
-                    let mut error = String::from("Unable to get file content for: ");
-                    error.push_str(path);
-                    Err(error)
+                EndpointType::Resource(resource_endpoint) => {
+                    return match self.endpoint_handler.execute(resource_endpoint, request, path_args) {
+                        Ok(response) => response,
+                        Err(errors) => {
+                            println!("--> invalid parameters: {:?}", errors);
+                            invalid_parameters(&errors).to_response(request.headers.get("accept").map(|it| it.as_str()))
+                        }
+                    };
+                }
+            };
+            self.serve_file(request, &asset_path, &endpoint.cache_policy)
+        }
+
+        /// The file, or the requested ranges of it, unless a precondition of the request
+        /// decides otherwise.
+        fn serve_file(&self, request: &HttpRequest, asset_path: &str, cache_policy: &CachePolicy) -> Response {
+            let content_type = self.endpoint_handler.mime_type(asset_path);
+            let precompressed = self.find_precompressed(request, asset_path, content_type);
+            let served_path = match &precompressed {
+                Some((sibling, _)) => sibling.as_str(),
+                None => asset_path,
+            };
+            let (file, metadata) = match open_file(served_path) {
+                Ok(res) => res,
+                Err(_) => {
+                    println!("--> not found");
+                    return Response::not_found();
+                }
+            };
+            let validators = Validators::from_metadata(&metadata);
+            let length = metadata.len();
+            // A file compressed on the fly is sent with an entity tag of its own, conditional
+            // requests are evaluated against it. Partial content and thus If-Range use the
+            // tag of the unencoded file.
+            let selected = match precompressed {
+                None => match self.encoding_on_the_fly(request, content_type, length) {
+                    Some(encoding) => validators.encoded(&encoding.to_string()),
+                    None => validators.clone(),
+                },
+                Some(_) => validators.clone(),
+            };
+            let mut response = Response::ok().header("ETag", &validators.etag);
+            if let Some(last_modified) = validators.last_modified_header() {
+                response = response.header("Last-Modified", &last_modified);
+            }
+            if let Some(cache_control) = cache_policy.header_value() {
+                response = response.header("Cache-Control", &cache_control);
+            }
+            match evaluate_preconditions(request, &selected) {
+                Precondition::Proceed => (),
+                Precondition::NotModified => {
+                    println!("--> not modified");
+                    return response.status(StatusCode::NOT_MODIFIED).header("ETag", &selected.etag);
+                }
+                Precondition::Failed => {
+                    println!("--> precondition failed");
+                    return Response::create(StatusCode::PRECONDITION_FAILED);
+                }
+            }
+            let response = response
+                .header("Content-Type", content_type)
+                .header("Accept-Ranges", "bytes");
+            if let Some((_, encoding)) = precompressed {
+                println!("--> precompressed {}", encoding);
+                return response
+                    .header("Content-Encoding", encoding.name())
+                    .body(ResponseBody::SizedStream(Box::new(file), length));
+            }
+            // Range is only defined for GET, a failed If-Range condition asks for the full content.
+            let range = match (request.general.method, request.headers.get("range")) {
+                (HttpMethod::Get, Some(range)) => match request.headers.get("if-range") {
+                    Some(if_range) if !if_range_matches(if_range, &validators) => RangeRequest::Full,
+                    _ => parse_range(range, length),
+                },
+                _ => RangeRequest::Full,
+            };
+            match range {
+                RangeRequest::Full => response.body(ResponseBody::SizedStream(Box::new(file), length)),
+                RangeRequest::Partial(ranges) => {
+                    println!("--> partial content {:?}", ranges);
+                    partial_content(response, file, length, &ranges).unwrap_or_else(|e| {
+                        println!("{}", e);
+                        Response::create(StatusCode::INTERNAL_SERVER_ERROR)
+                    })
                 }
+                RangeRequest::Unsatisfiable => {
+                    println!("--> range not satisfiable");
+                    Response::create(StatusCode::RANGE_NOT_SATISFIABLE).header("Content-Range", &format!("bytes */{}", length))
+                }
+            }
+        }
+
+        /// The encoding the full file is compressed with when it is sent, mirrors the decision
+        /// of `ResponseHandler::send`. HTTP/1.0 clients get files unencoded.
+        fn encoding_on_the_fly(&self, request: &HttpRequest, content_type: &str, length: u64) -> Option<ContentEncoding> {
+            let encoding = negotiate(request.headers.get("accept-encoding").map(|it| it.as_str()));
+            let encodes = encoding != ContentEncoding::Identity
+                && request.general.version != HttpVersion::OneZero
+                && length != 0
+                && self.compression.compresses(Some(content_type), Some(length));
+            encodes.then_some(encoding)
+        }
+
+        /// Finds a sibling of the file compressed with the best encoding the client accepts.
+        /// Range requests always address the original file, so they never use a sibling.
+        fn find_precompressed(&self, request: &HttpRequest, asset_path: &str, content_type: &str) -> Option<(String, ContentEncoding)> {
+            if !self.compression.serves_precompressed()
+                || !self.compression.compresses(Some(content_type), None)
+                || request.headers.contains_key("range")
+            {
+                return None;
             }
+            accepted(request.headers.get("accept-encoding").map(|it| it.as_str()))
+                .into_iter()
+                // Encodings ranked below identity are not worth a lookup.
+                .take_while(|encoding| *encoding != ContentEncoding::Identity)
+                .filter_map(|encoding| encoding.file_extension().map(|extension| (format!("{}.{}", asset_path, extension), encoding)))
+                // Symlinks are not followed, the sibling has to be a file inside the asset root itself.
+                .find(|(sibling, _)| fs::symlink_metadata(sibling).is_ok_and(|metadata| metadata.is_file()))
+        }
+    }
+
+    /// Closes a connection whose request was answered without being read. Closing a socket
+    /// with unread data resets the connection, and the client may lose the response, so the
+    /// sending side is shut down first and the request is drained for a short moment.
+    fn drain_and_close(mut stream: TcpStream) {
+        if stream.shutdown(Shutdown::Write).is_err() {
+            return;
+        }
+        let mut buf = [0u8; MESSAGE_SIZE];
+        let deadline = Instant::now() + DRAIN_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || stream.set_read_timeout(Some(remaining)).is_err() {
+                return;
+            }
+            match stream.read(&mut buf) {
+                Ok(0) => return,
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return,
+            }
+        }
+    }
+
+    /// Path used for routing, a trailing slash is ignored.
+    fn route_path(path: &str) -> &str {
+        match path.len() > 1 && path.ends_with("/") {
+            true => &path[..path.len() - 1],
+            false => path,
         }
     }
 }
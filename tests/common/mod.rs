@@ -0,0 +1,86 @@
+use rust_http::{EndpointHandler, ShutdownHandle, WebServer};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+
+static FIXTURE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Server on a free port serving the endpoints of a fixture directory, the directory
+/// is removed when the server is dropped.
+pub struct TestServer {
+    root: PathBuf,
+    port: u16,
+    shutdown: ShutdownHandle,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    /// Creates an empty fixture directory, `setup` fills it and registers the endpoints.
+    pub fn start<F>(name: &str, setup: F) -> TestServer
+    where
+        F: FnOnce(&Path) -> EndpointHandler,
+    {
+        let root = std::env::temp_dir().join(format!(
+            "rust-http-{}-{}-{}",
+            name,
+            std::process::id(),
+            FIXTURE_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&root).unwrap();
+        let endpoint_handler = setup(&root);
+        let mut server = WebServer::builder()
+            .port(0)
+            .endpoint_handler(endpoint_handler)
+            .build()
+            .unwrap();
+        let port = server.port().unwrap();
+        let shutdown = server.shutdown_handle();
+        let handle = thread::spawn(move || server.run().unwrap());
+        TestServer {
+            root,
+            port,
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// Sends the raw request target and returns the status code and body.
+    pub fn get(&self, target: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", target);
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response
+            .split(' ')
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .unwrap_or_else(|| panic!("invalid response for {}: {:?}", target, response));
+        let body = match response.split_once("\r\n\r\n") {
+            Some((_, body)) => String::from(body),
+            None => String::new(),
+        };
+        (status, body)
+    }
+
+    /// Asserts that the target is answered with 403 or 404 and without the word `secret`.
+    pub fn assert_refused(&self, target: &str) {
+        let (status, body) = self.get(target);
+        assert!(status == 403 || status == 404, "{} answered with {}", target, status);
+        assert!(!body.contains("secret"), "{} leaked the secret", target);
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.shutdown.shutdown();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::TestServer;
+use rust_http::{EndpointHandler, HttpRequest, Next, Response, StatusCode};
+use std::fs;
+use std::path::Path;
+
+/// Serves `<root>/storage` under `/storage`, a middleware on `/storage/private` answers
+/// every request below it with 403.
+fn start() -> TestServer {
+    TestServer::start("middleware", |root: &Path| {
+        let storage = root.join("storage");
+        fs::create_dir_all(storage.join("private")).unwrap();
+        fs::create_dir_all(storage.join("sub")).unwrap();
+        fs::write(storage.join("pub.txt"), "public").unwrap();
+        fs::write(storage.join("privateer.txt"), "pirate").unwrap();
+        fs::write(storage.join("private").join("secret.txt"), "secret").unwrap();
+
+        let mut endpoint_handler = EndpointHandler::create();
+        endpoint_handler
+            .register_assets(storage.to_string_lossy().into_owned(), String::from("storage"))
+            .unwrap();
+        endpoint_handler.register_middleware_for(
+            String::from("storage/private"),
+            Box::new(|_: &mut HttpRequest, _: Next| Response::create(StatusCode::FORBIDDEN)),
+        );
+        endpoint_handler
+    })
+}
+
+#[test]
+fn applies_middleware_below_the_prefix() {
+    let server = start();
+    assert_eq!(server.get("/storage/private/secret.txt").0, 403);
+    assert_eq!(server.get("/storage/private").0, 403);
+    assert_eq!(server.get("/storage/pub.txt"), (200, String::from("public")));
+    assert_eq!(server.get("/storage/privateer.txt"), (200, String::from("pirate")));
+    assert_eq!(server.get("/%73torage/pub.txt"), (200, String::from("public")));
+}
+
+#[test]
+fn applies_middleware_to_non_canonical_paths() {
+    let server = start();
+    for target in [
+        "/storage/./private/secret.txt",
+        "/storage/%70rivate/secret.txt",
+        "/storage/%70%72%69%76%61%74%65/secret.txt",
+        "/storage/pub.txt/../private/secret.txt",
+        "/storage/sub/../private/secret.txt",
+        "/storage//private/secret.txt",
+        "//storage/private/secret.txt",
+        "/storage/private%2fsecret.txt",
+        "/storage/sub%2f..%2fprivate%2fsecret.txt",
+        "/%73torage/private/secret.txt",
+    ] {
+        server.assert_refused(target);
+    }
+}
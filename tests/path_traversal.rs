@@ -0,0 +1,109 @@
+mod common;
+
+use common::TestServer;
+use rust_http::EndpointHandler;
+use std::fs;
+use std::path::Path;
+
+/// Serves `<root>/public` under `/assets`, next to it lies `<root>/secret.txt`
+/// that must never be reachable.
+fn start() -> TestServer {
+    TestServer::start("traversal", |root: &Path| {
+        let public = root.join("public");
+        fs::create_dir_all(public.join("sub")).unwrap();
+        fs::write(root.join("secret.txt"), "secret").unwrap();
+        fs::write(public.join("file.txt"), "public").unwrap();
+        fs::write(public.join("sub").join("inner.txt"), "inner").unwrap();
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret.txt"), public.join("escape.txt")).unwrap();
+            std::os::unix::fs::symlink(root, public.join("escape-dir")).unwrap();
+            std::os::unix::fs::symlink(public.join("file.txt"), public.join("link.txt")).unwrap();
+        }
+
+        let mut endpoint_handler = EndpointHandler::create();
+        endpoint_handler
+            .register_assets(public.to_string_lossy().into_owned(), String::from("assets"))
+            .unwrap();
+        endpoint_handler
+    })
+}
+
+#[test]
+fn serves_files_inside_the_root() {
+    let server = start();
+    assert_eq!(server.get("/assets/file.txt"), (200, String::from("public")));
+    assert_eq!(server.get("/assets/sub/inner.txt"), (200, String::from("inner")));
+    assert_eq!(server.get("/assets/sub/../file.txt"), (200, String::from("public")));
+    assert_eq!(server.get("/assets/./sub/./inner.txt"), (200, String::from("inner")));
+    assert_eq!(server.get("/assets/%66ile.txt"), (200, String::from("public")));
+}
+
+#[test]
+fn refuses_dot_dot_segments() {
+    let server = start();
+    for target in [
+        "/assets/../secret.txt",
+        "/assets/../../secret.txt",
+        "/assets/sub/../../secret.txt",
+        "/assets/sub/../../../../../../etc/passwd",
+        "/assets/..",
+        "/assets/../public/../secret.txt",
+    ] {
+        server.assert_refused(target);
+    }
+    assert_eq!(server.get("/assets/../secret.txt").0, 403);
+}
+
+#[test]
+fn refuses_percent_encoded_traversal() {
+    let server = start();
+    for target in [
+        "/assets/%2e%2e/secret.txt",
+        "/assets/%2E%2E/secret.txt",
+        "/assets/.%2e/secret.txt",
+        "/assets/%2e%2e%2fsecret.txt",
+        "/assets/%2e%2e%2Fsecret.txt",
+        "/assets/..%2f..%2fsecret.txt",
+        "/assets/sub%2f..%2f..%2fsecret.txt",
+        "/assets/%2e%2e%5csecret.txt",
+        "/assets/..%5csecret.txt",
+        "/assets/%252e%252e/secret.txt",
+        "/assets/file.txt%00.png",
+        "/assets/%2",
+        "/assets/%zz",
+    ] {
+        server.assert_refused(target);
+    }
+    assert_eq!(server.get("/assets/%2e%2e/secret.txt").0, 403);
+}
+
+#[test]
+fn refuses_absolute_and_backslash_paths() {
+    let server = start();
+    for target in [
+        "/assets//etc/passwd",
+        "/assets/%2fetc%2fpasswd",
+        "/assets/..\\secret.txt",
+        "/assets/sub\\..\\..\\secret.txt",
+    ] {
+        server.assert_refused(target);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn refuses_symlinks_leaving_the_root() {
+    let server = start();
+    assert_eq!(server.get("/assets/escape.txt").0, 403);
+    assert_eq!(server.get("/assets/escape-dir/secret.txt").0, 403);
+    assert_eq!(server.get("/assets/link.txt"), (200, String::from("public")));
+}
+
+#[test]
+fn answers_missing_files_with_not_found() {
+    let server = start();
+    assert_eq!(server.get("/assets/missing.txt").0, 404);
+    assert_eq!(server.get("/assets/sub").0, 404);
+    assert_eq!(server.get("/assetsfile.txt").0, 404);
+}